@@ -1,19 +1,31 @@
 # fastbash tab completion for bash
 
 _fastbash_completions() {
-    local cur prev commands scripts_dir
+    local cur prev commands scripts
     COMPREPLY=()
     cur="${COMP_WORDS[COMP_CWORD]}"
     prev="${COMP_WORDS[COMP_CWORD-1]}"
-    commands="ls rm help create edit"
+    commands="ls rm mv cp help alias export import import-dir lint log stats tag tags create edit info run show cat history revert templates trash restore git sync search grep"
 
-    scripts_dir="$HOME/.fastbash/scripts"
-    scripts=$(ls "$scripts_dir" 2>/dev/null)
+    scripts=$(fastbash ls --names 2>/dev/null)
 
     if [[ $COMP_CWORD -eq 1 ]]; then
         COMPREPLY=( $(compgen -W "$commands $scripts" -- "$cur") )
-    elif [[ $COMP_CWORD -eq 2 && $prev == "rm" || $COMP_CWORD -eq 2 && $prev == "edit" ]]; then
-        COMPREPLY=( $(compgen -W "$scripts" -- "$cur") )
+    elif [[ $COMP_CWORD -eq 2 ]]; then
+        case "$prev" in
+            rm|mv|cp|edit|info|run|show|cat|history|revert|export|lint|log)
+                COMPREPLY=( $(compgen -W "$scripts" -- "$cur") )
+                ;;
+            tag)
+                COMPREPLY=( $(compgen -W "add rm" -- "$cur") )
+                ;;
+            alias)
+                COMPREPLY=( $(compgen -W "ls add rm" -- "$cur") )
+                ;;
+            git)
+                COMPREPLY=( $(compgen -W "init remote status log" -- "$cur") )
+                ;;
+        esac
     fi
 }
 
@@ -0,0 +1,277 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{check_name, git, json, resolve_or_exit, store, COMMANDS};
+
+/// Per-store settings, kept next to the scripts so they travel with the
+/// store (and its git repository).
+pub const STORE_CONFIG: &str = ".config.toml";
+
+/// A short name for a script, with arguments that are always passed first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alias {
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StoreConfig {
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Alias>,
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(STORE_CONFIG)
+}
+
+pub fn load(root: &Path) -> StoreConfig {
+    let path = config_path(root);
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return StoreConfig::default();
+    };
+    toml::from_str(&contents).unwrap_or_else(|err| {
+        json::fail("invalid-config", format!("Invalid store config {}:\n{}", path.display(), err))
+    })
+}
+
+pub fn save(root: &Path, config: &StoreConfig) -> io::Result<()> {
+    fs::write(config_path(root), toml::to_string(config).expect("Failed to serialize store config"))
+}
+
+/// Aliases of the user store.
+pub fn aliases() -> BTreeMap<String, Alias> {
+    load(&store::get_scripts_dir()).aliases
+}
+
+/// Alias names grouped by the script they point to.
+pub fn by_target() -> BTreeMap<String, Vec<String>> {
+    let mut targets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, alias) in aliases() {
+        targets.entry(alias.target).or_default().push(name);
+    }
+    targets
+}
+
+/// Points aliases of `from` at `to` after a rename. Returns whether any
+/// alias changed.
+pub fn retarget(from: &str, to: &str) -> io::Result<bool> {
+    let root = store::get_scripts_dir();
+    let mut config = load(&root);
+    let mut changed = false;
+    for alias in config.aliases.values_mut().filter(|alias| alias.target == from) {
+        alias.target = to.to_string();
+        changed = true;
+    }
+    if changed {
+        save(&root, &config)?;
+    }
+    Ok(changed)
+}
+
+/// Turns `name args...` into the aliased script and its full argument list
+/// when `name` is an alias and not a script. Scripts always win.
+pub fn expand(name: &str, args: &[String]) -> (String, Vec<String>) {
+    if store::resolve(name).is_none()
+        && let Some(alias) = aliases().remove(name)
+    {
+        let mut full = alias.args;
+        full.extend_from_slice(args);
+        return (alias.target, full);
+    }
+    (name.to_string(), args.to_vec())
+}
+
+/// Splits `--args` the way a shell would: on whitespace, honouring quotes
+/// and backslash escapes.
+fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in '{}'", input));
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Quotes `arg` for display when it would not survive [`split_args`] as is.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"\\".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// `alias [ls]`, `alias add <name> <script> [--args "..."]`, `alias rm <name>`.
+pub fn alias_command(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None | Some("ls") if args.len() <= 1 => list(),
+        Some("add") => add(&args[1..]),
+        Some("rm") if args.len() == 2 => remove(&args[1]),
+        _ => json::fail(
+            "usage",
+            "Usage: fastbash alias [ls] | alias add <name> <script> [--args \"<args>\"] | alias rm <name>",
+        ),
+    }
+}
+
+fn list() {
+    let aliases = aliases();
+    if json::enabled() {
+        let objects: Vec<_> = aliases
+            .iter()
+            .map(|(name, alias)| json!({ "name": name, "target": alias.target, "args": alias.args }))
+            .collect();
+        json::print(&objects.into());
+        return;
+    }
+    if aliases.is_empty() {
+        println!("No aliases (add one with `fastbash alias add <name> <script>`)");
+        return;
+    }
+    for (name, alias) in aliases {
+        let mut line = format!("{:<12} -> {}", name, alias.target);
+        for arg in &alias.args {
+            line.push(' ');
+            line.push_str(&quote(arg));
+        }
+        println!("{}", line);
+    }
+}
+
+fn add(args: &[String]) {
+    let mut positional = Vec::new();
+    let mut fixed = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--args" {
+            let Some(value) = iter.next() else {
+                json::fail("usage", "--args needs a value");
+            };
+            fixed = Some(value.as_str());
+        } else if let Some(value) = arg.strip_prefix("--args=") {
+            fixed = Some(value);
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    let [name, target] = positional[..] else {
+        json::fail("usage", "Usage: fastbash alias add <name> <script> [--args \"<args>\"]");
+    };
+    let fixed = split_args(fixed.unwrap_or("")).unwrap_or_else(|err| json::fail("invalid-argument", err));
+
+    check_name(name);
+    if COMMANDS.contains(&name.split('/').next().unwrap_or(name)) {
+        json::fail("invalid-name", format!("'{}' is a fastbash command and cannot be used as an alias", name));
+    }
+    if store::resolve(name).is_some() {
+        let message = format!("'{}' is already a script; an alias with that name would never be used", name);
+        json::fail("already-exists", message);
+    }
+    let target = resolve_or_exit(target).name;
+
+    let root = store::ensure_scripts_dir();
+    let mut config = load(&root);
+    let replaced = config.aliases.insert(name.to_string(), Alias { target: target.clone(), args: fixed });
+    save(&root, &config).unwrap_or_else(|err| json::fail("io", format!("Failed to save aliases: {}", err)));
+    git::auto_commit(&root, &[STORE_CONFIG], &format!("alias add {}", name));
+    let object = json!({ "action": "alias add", "name": name, "target": target, "replaced": replaced.is_some() });
+    match replaced {
+        Some(_) => json::done(object, format_args!("Alias '{}' now points to '{}'", name, target)),
+        None => json::done(object, format_args!("Added alias '{}' for '{}'", name, target)),
+    }
+}
+
+fn remove(name: &str) {
+    let root = store::get_scripts_dir();
+    let mut config = load(&root);
+    if config.aliases.remove(name).is_none() {
+        json::fail("not-found", format!("Alias '{}' not found", name));
+    }
+    save(&root, &config).unwrap_or_else(|err| json::fail("io", format!("Failed to save aliases: {}", err)));
+    git::auto_commit(&root, &[STORE_CONFIG], &format!("alias rm {}", name));
+    json::done(json!({ "action": "alias rm", "name": name }), format_args!("Removed alias '{}'", name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(input: &str) -> Vec<String> {
+        split_args(input).unwrap()
+    }
+
+    #[test]
+    fn splits_on_any_whitespace() {
+        assert_eq!(split("  -v   --env prod\t-n "), ["-v", "--env", "prod", "-n"]);
+        assert!(split("").is_empty());
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_spaces_together() {
+        assert_eq!(split(r#"--msg "hello world" 'a b'"#), ["--msg", "hello world", "a b"]);
+        assert_eq!(split(r#"--name="two words""#), ["--name=two words"]);
+    }
+
+    #[test]
+    fn empty_quotes_are_an_argument() {
+        assert_eq!(split(r#"'' """#), ["", ""]);
+        assert_eq!(split(r#"a "" b"#), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn backslashes_escape_outside_single_quotes() {
+        assert_eq!(split(r"a\ b c"), ["a b", "c"]);
+        assert_eq!(split(r#""say \"hi\"""#), [r#"say "hi""#]);
+        assert_eq!(split(r"'C:\dir'"), [r"C:\dir"]);
+    }
+
+    #[test]
+    fn other_quote_kind_is_literal_inside_quotes() {
+        assert_eq!(split(r#""it's" 'say "x"'"#), ["it's", r#"say "x""#]);
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        assert!(split_args(r#"--msg "oops"#).is_err());
+        assert!(split_args("'").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read, Write},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+};
+
+use chrono::{DateTime, Local};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use similar::{ChangeTag, TextDiff};
+
+use crate::{
+    git, history, json, meta::ScriptMeta, new_name_error, resolve_or_exit, search, store, target_path_error, term,
+};
+
+const MANIFEST: &str = "manifest.toml";
+const SCRIPTS_PREFIX: &str = "scripts/";
+const FORMAT_VERSION: u32 = 1;
+
+/// Describes the contents of a bundle; stored as `manifest.toml` next to the
+/// `scripts/` directory in the archive.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    created_at: DateTime<Local>,
+    #[serde(default)]
+    scripts: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    name: String,
+    sha256: String,
+    mode: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpreter: Option<String>,
+}
+
+/// `export [names...] [--tag <tag>]... -o <file>`; exports everything when
+/// neither names nor tags are given.
+pub fn export_command(args: &[String]) {
+    let usage = "Usage: fastbash export [<script>...] [--tag <tag>]... -o <bundle.tar.gz>";
+    let mut names = Vec::new();
+    let mut tags = Vec::new();
+    let mut output = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next().cloned().unwrap_or_else(|| json::fail("usage", usage))
+        };
+        match arg.as_str() {
+            "-o" | "--output" => output = Some(value()),
+            "--tag" | "-t" => tags.push(value().to_lowercase()),
+            _ if !arg.starts_with('-') => names.push(arg.clone()),
+            _ => json::fail("usage", usage),
+        }
+    }
+    let Some(output) = output else {
+        json::fail("usage", usage);
+    };
+
+    let mut scripts: Vec<store::Script> = names.iter().map(|name| resolve_or_exit(name)).collect();
+    if !tags.is_empty() || names.is_empty() {
+        scripts.extend(search::tagged_scripts(&tags).into_iter().map(|(script, _)| script));
+    }
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+    scripts.dedup_by(|a, b| a.name == b.name);
+    if scripts.is_empty() {
+        json::fail("not-found", "No scripts to export");
+    }
+
+    if let Err(err) = write_bundle(Path::new(&output), &scripts) {
+        json::fail("io", format!("Failed to write '{}': {}", output, err));
+    }
+    let names: Vec<&str> = scripts.iter().map(|script| script.name.as_str()).collect();
+    json::done(
+        json!({ "action": "export", "path": output, "scripts": names }),
+        format_args!("Exported {} script(s) to {}", scripts.len(), output),
+    );
+}
+
+fn write_bundle(output: &Path, scripts: &[store::Script]) -> io::Result<()> {
+    let mut manifest = Manifest { format: FORMAT_VERSION, created_at: Local::now(), scripts: Vec::new() };
+    let mut contents = Vec::new();
+    for script in scripts {
+        let content = fs::read(&script.path)?;
+        let mode = fs::metadata(&script.path)?.permissions().mode() & 0o777;
+        let meta = ScriptMeta::read(&script.path);
+        manifest.scripts.push(Entry {
+            name: script.name.clone(),
+            sha256: history::sha256_hex(&content),
+            mode,
+            interpreter: meta.interpreter().map(str::to_string),
+            description: meta.description,
+            tags: meta.tags,
+        });
+        contents.push(content);
+    }
+
+    let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+    let manifest_toml = toml::to_string(&manifest).expect("Failed to serialize manifest");
+    append(&mut archive, MANIFEST, manifest_toml.as_bytes(), 0o644)?;
+    for (entry, content) in manifest.scripts.iter().zip(&contents) {
+        append(&mut archive, &format!("{}{}", SCRIPTS_PREFIX, entry.name), content, entry.mode)?;
+    }
+    archive.into_inner()?.finish()?.flush()
+}
+
+fn append<W: Write>(archive: &mut tar::Builder<W>, path: &str, data: &[u8], mode: u32) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(mode);
+    header.set_mtime(Local::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, path, data)
+}
+
+fn read_bundle(path: &Path) -> io::Result<(Manifest, HashMap<String, Vec<u8>>)> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+    let mut manifest = None;
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if name == MANIFEST {
+            let text = String::from_utf8_lossy(&data);
+            manifest = Some(toml::from_str::<Manifest>(&text).map_err(|err| io::Error::other(err.to_string()))?);
+        } else if let Some(script) = name.strip_prefix(SCRIPTS_PREFIX) {
+            files.insert(script.to_string(), data);
+        }
+    }
+    let manifest = manifest.ok_or_else(|| io::Error::other("no manifest.toml; not a fastbash bundle"))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(io::Error::other(format!(
+            "bundle format {} is newer than this fastbash supports ({})",
+            manifest.format, FORMAT_VERSION
+        )));
+    }
+    Ok((manifest, files))
+}
+
+/// What to do with a bundled script whose name is taken.
+enum Resolution {
+    Skip,
+    Overwrite,
+    Rename(String),
+}
+
+fn ask(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    // End of input counts as an empty answer
+    let _ = io::stdin().read_line(&mut answer);
+    answer.trim().to_string()
+}
+
+fn resolve_conflict(name: &str, current: &[u8], incoming: &[u8], root: &Path) -> Resolution {
+    println!("Script '{}' already exists and differs from the bundled version:", name);
+    print_diff(&String::from_utf8_lossy(current), &String::from_utf8_lossy(incoming), name);
+    loop {
+        match ask("[s]kip, [o]verwrite or [r]ename the imported script? [s] ").to_lowercase().as_str() {
+            "" | "s" | "skip" => return Resolution::Skip,
+            "o" | "overwrite" => return Resolution::Overwrite,
+            "r" | "rename" => {
+                let new_name = ask("New name: ");
+                if new_name.is_empty() {
+                    continue;
+                }
+                if let Some(err) = new_name_error(&new_name).or_else(|| target_path_error(root, &new_name)) {
+                    println!("{}", err);
+                    continue;
+                }
+                if root.join(&new_name).exists() {
+                    println!("Script '{}' already exists too", new_name);
+                    continue;
+                }
+                return Resolution::Rename(new_name);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn print_diff(old: &str, new: &str, name: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    let color = term::color_enabled();
+    println!("--- {} (current)\n+++ {} (bundle)", name, name);
+    for group in diff.grouped_ops(3) {
+        for op in group {
+            for change in diff.iter_changes(&op) {
+                let (sign, paint) = match change.tag() {
+                    ChangeTag::Delete => ("-", "\x1b[31m"),
+                    ChangeTag::Insert => ("+", "\x1b[32m"),
+                    ChangeTag::Equal => (" ", ""),
+                };
+                let line = change.to_string_lossy();
+                let line = line.trim_end_matches('\n');
+                if color && !paint.is_empty() {
+                    println!("{}{}{}\x1b[0m", paint, sign, line);
+                } else {
+                    println!("{}{}", sign, line);
+                }
+            }
+        }
+    }
+}
+
+/// Writes an imported script with its bundled mode. The owner always keeps
+/// read, write and execute permission so the script can be run and edited.
+fn write_script(path: &Path, content: &[u8], mode: u32) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777 | 0o700))
+}
+
+/// `import <bundle>` adds the bundled scripts to the user store, asking what
+/// to do about each script that already exists with other content.
+pub fn import_command(args: &[String]) {
+    let [bundle] = args else {
+        json::fail("usage", "Usage: fastbash import <bundle.tar.gz>");
+    };
+    let (manifest, mut files) = read_bundle(Path::new(bundle))
+        .unwrap_or_else(|err| json::fail("io", format!("Failed to read bundle '{}': {}", bundle, err)));
+
+    let root = store::ensure_scripts_dir();
+    let mut imported: Vec<String> = Vec::new();
+    let mut skipped = 0;
+    for entry in &manifest.scripts {
+        let Some(content) = files.remove(&entry.name) else {
+            eprintln!("Skipping '{}': missing from the bundle", entry.name);
+            skipped += 1;
+            continue;
+        };
+        if let Some(err) = new_name_error(&entry.name) {
+            eprintln!("Skipping bundled script: {}", err);
+            skipped += 1;
+            continue;
+        }
+        if history::sha256_hex(&content) != entry.sha256 {
+            eprintln!("Skipping '{}': checksum does not match the manifest", entry.name);
+            skipped += 1;
+            continue;
+        }
+
+        let mut name = entry.name.clone();
+        let existing = root.join(&name);
+        if existing.is_file() {
+            let current = fs::read(&existing).unwrap_or_default();
+            if current == content {
+                if !json::enabled() {
+                    println!("'{}' is already up to date", name);
+                }
+                continue;
+            }
+            match resolve_conflict(&name, &current, &content, &root) {
+                Resolution::Skip => {
+                    skipped += 1;
+                    continue;
+                }
+                Resolution::Overwrite => history::record_or_warn(&store::user_script(&name), "external"),
+                Resolution::Rename(new_name) => name = new_name,
+            }
+        } else if let Some(err) = target_path_error(&root, &name) {
+            eprintln!("Skipping '{}': {}", name, err);
+            skipped += 1;
+            continue;
+        }
+
+        // Keep going on failure, so what was imported still gets committed
+        if let Err(err) = write_script(&root.join(&name), &content, entry.mode) {
+            eprintln!("Failed to write '{}': {}", name, err);
+            skipped += 1;
+            continue;
+        }
+        history::record_or_warn(&store::user_script(&name), "import");
+        if !json::enabled() {
+            println!("Imported '{}'", name);
+        }
+        imported.push(name);
+    }
+
+    if !imported.is_empty() {
+        let paths: Vec<&str> = imported.iter().map(String::as_str).collect();
+        let file_name = Path::new(bundle).file_name().map_or(bundle.clone(), |f| f.to_string_lossy().into_owned());
+        git::auto_commit(&root, &paths, &format!("import {}", file_name));
+    }
+    json::done(
+        json!({ "action": "import", "imported": imported, "skipped": skipped }),
+        format_args!("Imported {} script(s), skipped {}", imported.len(), skipped),
+    );
+}
@@ -0,0 +1,68 @@
+use std::{fs, path::PathBuf, sync::LazyLock};
+
+use serde::Deserialize;
+
+use crate::json;
+
+/// User settings read from `$XDG_CONFIG_HOME/fastbash/config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Root of the fastbash data directory (scripts, templates, ...).
+    pub home: Option<String>,
+    /// Where scripts are stored. Defaults to `<home>/scripts`.
+    pub scripts_dir: Option<String>,
+    /// Read-only shared store. Defaults to `/usr/share/fastbash/scripts`.
+    pub system_dir: Option<String>,
+    /// Fills the `{{author}}` template placeholder. Defaults to git's `user.name`.
+    pub author: Option<String>,
+    /// What to do when a script name is not found.
+    pub suggestions: Suggestions,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Suggestions {
+    /// Print a plain "not found" error.
+    Off,
+    /// List the closest script names.
+    #[default]
+    Suggest,
+    /// Suggest, and run a script when the name is a prefix of exactly one script.
+    Auto,
+}
+
+static CONFIG: LazyLock<Config> = LazyLock::new(Config::load);
+
+pub fn get() -> &'static Config {
+    &CONFIG
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("fastbash/config.toml"))
+}
+
+impl Config {
+    fn load() -> Config {
+        let Some(path) = config_path() else {
+            return Config::default();
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Config::default(),
+        };
+        match toml::from_str(&contents) {
+            Ok(config) => config,
+            Err(err) => json::fail("invalid-config", format!("Invalid config file {}:\n{}", path.display(), err)),
+        }
+    }
+}
+
+/// Expands a leading `~/` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().expect("Could not determine home directory").join(rest),
+        None if path == "~" => dirs::home_dir().expect("Could not determine home directory"),
+        None => PathBuf::from(path),
+    }
+}
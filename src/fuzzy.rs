@@ -0,0 +1,51 @@
+/// Scores how well `query` matches `text` as a case-insensitive subsequence,
+/// fzf style. Returns `None` when some query character is missing or the
+/// characters are scattered so widely that the match is meaningless.
+/// Matches at word starts and runs of consecutive characters score higher;
+/// gaps and long texts score lower.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0i64;
+    let mut q = 0;
+    let mut last_match: Option<usize> = None;
+    for (i, &c) in text.iter().enumerate() {
+        if q == query.len() {
+            break;
+        }
+        if c != query[q] {
+            continue;
+        }
+        score += 10;
+        let boundary = i == 0 || matches!(text[i - 1], '/' | '-' | '_' | ' ' | '.');
+        if boundary {
+            score += 8;
+        }
+        match last_match {
+            Some(last) if last + 1 == i => score += 6,
+            Some(last) => score -= (i - last - 1).min(10) as i64,
+            None => score -= i.min(10) as i64,
+        }
+        last_match = Some(i);
+        q += 1;
+    }
+    if q < query.len() {
+        return None;
+    }
+
+    let joined: String = query.iter().collect();
+    let haystack: String = text.iter().collect();
+    if haystack == joined {
+        score += 50;
+    } else if haystack.starts_with(&joined) {
+        score += 25;
+    } else if haystack.contains(&joined) {
+        score += 15;
+    }
+    let score = score - (text.len() as i64 / 10);
+    (score > 0).then_some(score)
+}
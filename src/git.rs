@@ -0,0 +1,162 @@
+use std::{
+    fs,
+    path::Path,
+    process::{exit, Command, Output},
+};
+
+use crate::{json, store};
+
+const GITIGNORE: &str = ".trash/\n.history/\n.data/\n";
+
+/// Runs git inside `root`. When the user has no identity configured (e.g. in
+/// CI), a fallback one is supplied so automatic commits still work.
+fn git(root: &Path, args: &[&str]) -> std::io::Result<Output> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(root);
+    for (key, fallback) in [("user.name", "fastbash"), ("user.email", "fastbash@localhost")] {
+        let configured = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(["config", "--get", key])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !configured {
+            cmd.arg("-c").arg(format!("{}={}", key, fallback));
+        }
+    }
+    cmd.args(args).output()
+}
+
+/// Like [`git`], but exits with git's error output on failure.
+fn git_or_exit(root: &Path, args: &[&str]) -> String {
+    match git(root, args) {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            json::fail("command-failed", format!("git {} failed:\n{}", args.join(" "), stderr.trim_end()))
+        }
+        Err(err) => json::fail("command-failed", format!("Failed to run git: {}", err)),
+    }
+}
+
+pub fn is_repo(root: &Path) -> bool {
+    root.join(".git").exists()
+}
+
+/// Commits changes to `paths` (relative to the store) if the store is a git
+/// repository. Failures are reported but never abort the command.
+pub fn auto_commit(root: &Path, paths: &[&str], message: &str) {
+    if !is_repo(root) {
+        return;
+    }
+    let mut add = vec!["add", "-A", "--"];
+    add.extend_from_slice(paths);
+    let result = git(root, &add).and_then(|output| {
+        if !output.status.success() {
+            return Ok(output);
+        }
+        let staged = git(root, &["diff", "--cached", "--quiet"])?;
+        if staged.status.success() {
+            // Nothing changed
+            return Ok(staged);
+        }
+        git(root, &["commit", "-q", "-m", message])
+    });
+    match result {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => eprintln!(
+            "Warning: git commit of '{}' failed:\n{}",
+            message,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ),
+        Err(err) => eprintln!("Warning: failed to run git: {}", err),
+    }
+}
+
+/// Commits a change to one script in its store.
+pub fn commit_script(script: &store::Script, action: &str) {
+    auto_commit(&script.root, &[&script.name], &format!("{} {}", action, script.name));
+}
+
+/// `fastbash git init` sets up the repository; anything else is passed to git
+/// inside the store, e.g. `fastbash git remote add origin <url>`.
+pub fn git_command(args: &[String]) {
+    let root = store::ensure_scripts_dir();
+    match args.first().map(String::as_str) {
+        Some("init") if args.len() == 1 => init(&root),
+        Some(_) => {
+            let status = Command::new("git").arg("-C").arg(&root).args(args).status();
+            match status {
+                Ok(status) => exit(status.code().unwrap_or(1)),
+                Err(err) => json::fail("command-failed", format!("Failed to run git: {}", err)),
+            }
+        }
+        None => json::fail("usage", "Usage: fastbash git init | fastbash git <git args...>"),
+    }
+}
+
+fn init(root: &Path) {
+    if is_repo(root) {
+        println!("{} is already a git repository", root.display());
+        return;
+    }
+    git_or_exit(root, &["init", "-q"]);
+    let gitignore = root.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, GITIGNORE).expect("Failed to write .gitignore");
+    }
+    auto_commit(root, &["."], "Initial import of fastbash scripts");
+    println!("Initialized git repository in {}", root.display());
+}
+
+/// `fastbash sync`: commit local changes, merge the remote branch, push.
+/// On a merge conflict the merge is aborted so the store stays usable.
+pub fn sync_command(args: &[String]) {
+    let remote = match args {
+        [] => "origin",
+        [remote] => remote.as_str(),
+        _ => json::fail("usage", "Usage: fastbash sync [remote]"),
+    };
+    let root = store::get_scripts_dir();
+    if !is_repo(&root) {
+        json::fail("not-a-repository", "The script store is not a git repository; run `fastbash git init` first");
+    }
+    let remotes = git_or_exit(&root, &["remote"]);
+    if !remotes.lines().any(|line| line == remote) {
+        let message = format!(
+            "No git remote '{}' configured; add one with `fastbash git remote add {} <url>`",
+            remote, remote
+        );
+        json::fail("not-found", message);
+    }
+
+    auto_commit(&root, &["."], "sync local changes");
+    let branch = git_or_exit(&root, &["rev-parse", "--abbrev-ref", "HEAD"]).trim().to_string();
+    git_or_exit(&root, &["fetch", "-q", remote]);
+
+    let upstream = format!("{}/{}", remote, branch);
+    let has_upstream = git(&root, &["rev-parse", "--verify", "-q", &upstream])
+        .is_ok_and(|output| output.status.success());
+    if has_upstream {
+        // Stores initialised separately share no history until their first sync
+        let merge = git(&root, &["merge", "-q", "--no-edit", "--allow-unrelated-histories", &upstream])
+            .expect("Failed to run git");
+        if !merge.status.success() {
+            let conflicts = git_or_exit(&root, &["diff", "--name-only", "--diff-filter=U"]);
+            let _ = git(&root, &["merge", "--abort"]);
+            if conflicts.trim().is_empty() {
+                let stderr = String::from_utf8_lossy(&merge.stderr);
+                json::fail("command-failed", format!("Merging {} failed:\n{}", upstream, stderr.trim_end()));
+            }
+            let mut message = format!("Sync stopped: these scripts changed both locally and on {}:\n", upstream);
+            for script in conflicts.lines() {
+                message.push_str(&format!("    {}\n", script));
+            }
+            message.push_str("Your scripts were left as they were. Reconcile them and run `fastbash sync` again.");
+            json::fail("conflict", message);
+        }
+    }
+
+    git_or_exit(&root, &["push", "-q", "-u", remote, &branch]);
+    println!("Synced with {}", upstream);
+}
@@ -0,0 +1,264 @@
+/// A small, line-oriented syntax highlighter for the languages scripts are
+/// usually written in. It colors comments, strings, keywords, variables and
+/// numbers; it does not try to be a parser.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Shell,
+    Python,
+    JavaScript,
+    Ruby,
+    Perl,
+    Plain,
+}
+
+const COMMENT: &str = "\x1b[90m";
+const STRING: &str = "\x1b[32m";
+const KEYWORD: &str = "\x1b[1;35m";
+const VARIABLE: &str = "\x1b[36m";
+const NUMBER: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+const SHELL_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac", "in",
+    "function", "return", "local", "export", "readonly", "declare", "set", "unset", "shift", "exit",
+    "source", "trap", "eval", "exec", "select", "break", "continue",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while",
+    "with", "yield",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete",
+    "do", "else", "export", "false", "finally", "for", "function", "if", "import", "in",
+    "instanceof", "let", "new", "null", "of", "require", "return", "switch", "this", "throw",
+    "true", "try", "typeof", "undefined", "var", "while", "yield",
+];
+const RUBY_KEYWORDS: &[&str] = &[
+    "begin", "break", "case", "class", "def", "do", "else", "elsif", "end", "ensure", "false",
+    "for", "if", "in", "module", "next", "nil", "puts", "raise", "require", "rescue", "return",
+    "self", "then", "true", "unless", "until", "when", "while", "yield",
+];
+const PERL_KEYWORDS: &[&str] = &[
+    "else", "elsif", "for", "foreach", "if", "last", "local", "my", "next", "our", "package",
+    "print", "return", "sub", "unless", "until", "use", "while",
+];
+
+impl Language {
+    /// Picks a language from the interpreter named in the shebang.
+    pub fn from_interpreter(interpreter: Option<&str>) -> Language {
+        let Some(interpreter) = interpreter else {
+            return Language::Plain;
+        };
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        match name {
+            "sh" | "bash" | "zsh" | "ksh" | "dash" | "ash" | "mksh" | "fish" => Language::Shell,
+            "python" | "pypy" => Language::Python,
+            "node" | "nodejs" | "deno" | "bun" => Language::JavaScript,
+            "ruby" => Language::Ruby,
+            "perl" => Language::Perl,
+            _ => Language::Plain,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Shell => SHELL_KEYWORDS,
+            Language::Python => PYTHON_KEYWORDS,
+            Language::JavaScript => JS_KEYWORDS,
+            Language::Ruby => RUBY_KEYWORDS,
+            Language::Perl => PERL_KEYWORDS,
+            Language::Plain => &[],
+        }
+    }
+
+    fn line_comment(self) -> &'static str {
+        match self {
+            Language::JavaScript => "//",
+            _ => "#",
+        }
+    }
+
+    fn has_sigil_variables(self) -> bool {
+        matches!(self, Language::Shell | Language::Perl)
+    }
+}
+
+/// Returns `source` with ANSI color codes added.
+pub fn highlight(source: &str, language: Language) -> String {
+    let mut out = String::with_capacity(source.len() * 2);
+    // Closing delimiter of a string that continues onto the next line
+    let mut open_string: Option<String> = None;
+
+    for (i, line) in source.split_inclusive('\n').enumerate() {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        if i == 0 && text.starts_with("#!") {
+            paint(&mut out, COMMENT, text);
+        } else {
+            highlight_line(&mut out, text, language, &mut open_string);
+        }
+        out.push_str(newline);
+    }
+    out
+}
+
+fn paint(out: &mut String, color: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    out.push_str(color);
+    out.push_str(text);
+    out.push_str(RESET);
+}
+
+fn highlight_line(out: &mut String, line: &str, language: Language, open_string: &mut Option<String>) {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let byte_at = |i: usize| chars.get(i).map_or(line.len(), |(b, _)| *b);
+    let mut i = 0;
+
+    if let Some(delim) = open_string.take() {
+        let end = find_string_end(line, 0, &delim, language);
+        match end {
+            Some(end) => {
+                paint(out, STRING, &line[..end]);
+                i = chars.iter().position(|(b, _)| *b >= end).unwrap_or(chars.len());
+            }
+            None => {
+                paint(out, STRING, line);
+                *open_string = Some(delim);
+                return;
+            }
+        }
+    }
+
+    let comment = language.line_comment();
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let rest = &line[start..];
+        let prev = if i == 0 { ' ' } else { chars[i - 1].1 };
+
+        if rest.starts_with(comment) && (language != Language::Shell || prev.is_whitespace() || prev == ';') {
+            paint(out, COMMENT, rest);
+            return;
+        }
+
+        if let Some(delim) = string_opener(rest, language) {
+            let body_start = start + delim.len();
+            match find_string_end(line, body_start, delim, language) {
+                Some(end) => {
+                    paint(out, STRING, &line[start..end]);
+                    i = chars.iter().position(|(b, _)| *b >= end).unwrap_or(chars.len());
+                }
+                None => {
+                    paint(out, STRING, rest);
+                    *open_string = Some(delim.to_string());
+                    return;
+                }
+            }
+            continue;
+        }
+
+        if c == '$' && language.has_sigil_variables() {
+            let end = variable_end(&chars, i);
+            if end > i + 1 {
+                paint(out, VARIABLE, &line[start..byte_at(end)]);
+                i = end;
+                continue;
+            }
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let mut end = i;
+            while end < chars.len() && (chars[end].1.is_alphanumeric() || chars[end].1 == '_') {
+                end += 1;
+            }
+            let word = &line[start..byte_at(end)];
+            let standalone = !matches!(prev, '-' | '.' | '/' | '$');
+            if standalone && language.keywords().contains(&word) {
+                paint(out, KEYWORD, word);
+            } else {
+                out.push_str(word);
+            }
+            i = end;
+            continue;
+        }
+
+        if c.is_ascii_digit() && !prev.is_alphanumeric() && prev != '_' {
+            let mut end = i;
+            while end < chars.len() && (chars[end].1.is_ascii_alphanumeric() || chars[end].1 == '.') {
+                end += 1;
+            }
+            paint(out, NUMBER, &line[start..byte_at(end)]);
+            i = end;
+            continue;
+        }
+
+        out.push(c);
+        i += 1;
+    }
+}
+
+fn string_opener(rest: &str, language: Language) -> Option<&'static str> {
+    if language == Language::Python {
+        for delim in ["\"\"\"", "'''"] {
+            if rest.starts_with(delim) {
+                return Some(delim);
+            }
+        }
+    }
+    match rest.chars().next()? {
+        '"' => Some("\""),
+        '\'' => Some("'"),
+        '`' if matches!(language, Language::Shell | Language::JavaScript | Language::Ruby | Language::Perl) => {
+            Some("`")
+        }
+        _ => None,
+    }
+}
+
+/// Byte index just past the closing `delim`, searching from `from`.
+fn find_string_end(line: &str, from: usize, delim: &str, language: Language) -> Option<usize> {
+    // Shell single quotes have no escapes
+    let escapes = !(language == Language::Shell && delim == "'");
+    let mut escaped = false;
+    for (offset, c) in line[from..].char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if escapes && c == '\\' {
+            escaped = true;
+            continue;
+        }
+        if line[from + offset..].starts_with(delim) {
+            return Some(from + offset + delim.len());
+        }
+    }
+    None
+}
+
+/// End (char index) of a `$name`, `${...}` or `$1`/`$@`-style variable starting at `i`.
+fn variable_end(chars: &[(usize, char)], i: usize) -> usize {
+    let Some(&(_, next)) = chars.get(i + 1) else {
+        return i + 1;
+    };
+    if next == '{' {
+        let mut end = i + 2;
+        while end < chars.len() && chars[end].1 != '}' {
+            end += 1;
+        }
+        return (end + 1).min(chars.len());
+    }
+    if next.is_ascii_digit() || "@*#?$!-".contains(next) {
+        return i + 2;
+    }
+    let mut end = i + 1;
+    while end < chars.len() && (chars[end].1.is_alphanumeric() || chars[end].1 == '_') {
+        end += 1;
+    }
+    end
+}
@@ -0,0 +1,331 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+
+use crate::{git, json, make_executable, resolve_or_exit, store};
+
+const HISTORY_DIR: &str = ".history";
+
+/// One saved version of a script. The content lives in the object store
+/// under its hash, so identical versions share storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub hash: String,
+    pub saved_at: DateTime<Local>,
+    pub action: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    #[serde(default)]
+    revisions: Vec<Revision>,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn history_dir(root: &Path) -> PathBuf {
+    root.join(HISTORY_DIR)
+}
+
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    history_dir(root).join("objects").join(&hash[..2]).join(&hash[2..])
+}
+
+/// Where the revision list of `name` is kept: `.history/index/<name>.toml`.
+pub fn index_path(root: &Path, name: &str) -> PathBuf {
+    history_dir(root).join("index").join(format!("{}.toml", name))
+}
+
+fn load_index(root: &Path, name: &str) -> Index {
+    fs::read_to_string(index_path(root, name))
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(root: &Path, name: &str, index: &Index) -> io::Result<()> {
+    let path = index_path(root, name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, toml::to_string(index).expect("Failed to serialize history index"))
+}
+
+/// Where the revision list of a removed script waits for `restore`:
+/// `.history/trash/<trash id>.toml`.
+fn trashed_index_path(root: &Path, id: &str) -> PathBuf {
+    history_dir(root).join("trash").join(format!("{}.toml", id))
+}
+
+fn move_index(root: &Path, source: &Path, target: &Path, copy: bool) -> io::Result<()> {
+    if !source.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if copy {
+        fs::copy(source, target).map(|_| ())
+    } else {
+        fs::rename(source, target)?;
+        store::prune_empty_dirs(&history_dir(root), source);
+        Ok(())
+    }
+}
+
+/// Gives the revisions of `from` to `to`, e.g. after a rename. With `copy`
+/// the original keeps its history too; the objects are shared either way.
+pub fn transfer(root: &Path, from: &str, to: &str, copy: bool) -> io::Result<()> {
+    move_index(root, &index_path(root, from), &index_path(root, to), copy)
+}
+
+/// Sets the history of `name` aside with its trash entry, so a new script
+/// with the same name starts without it.
+pub fn trash(root: &Path, name: &str, id: &str) -> io::Result<()> {
+    move_index(root, &index_path(root, name), &trashed_index_path(root, id), false)
+}
+
+/// Gives a script restored from trash entry `id` its history back.
+pub fn restore(root: &Path, id: &str, name: &str) -> io::Result<()> {
+    move_index(root, &trashed_index_path(root, id), &index_path(root, name), false)
+}
+
+/// Drops the history of `name`, including every saved version no other
+/// script shares, e.g. after `rm --force`.
+pub fn forget(root: &Path, name: &str) -> io::Result<()> {
+    remove_index(root, &index_path(root, name))
+}
+
+/// Drops the history set aside with trash entry `id` when the trash is emptied.
+pub fn forget_trashed(root: &Path, id: &str) -> io::Result<()> {
+    remove_index(root, &trashed_index_path(root, id))
+}
+
+fn remove_index(root: &Path, path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => store::prune_empty_dirs(&history_dir(root), path),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    }
+    collect_garbage(root)
+}
+
+/// Deletes objects that no index, live or trashed, refers to any more.
+fn collect_garbage(root: &Path) -> io::Result<()> {
+    let dir = history_dir(root);
+    let mut indexes = Vec::new();
+    collect_files(&dir.join("index"), &mut indexes);
+    collect_files(&dir.join("trash"), &mut indexes);
+    let mut referenced = HashSet::new();
+    for path in indexes {
+        // An index we cannot read may still need its objects, so keep them all
+        let index: Index = toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        referenced.extend(index.revisions.into_iter().map(|revision| revision.hash));
+    }
+
+    let mut objects = Vec::new();
+    collect_files(&dir.join("objects"), &mut objects);
+    for object in objects {
+        let hash = object
+            .strip_prefix(dir.join("objects"))
+            .map(|relative| relative.to_string_lossy().replace('/', ""))
+            .unwrap_or_default();
+        if !referenced.contains(&hash) {
+            fs::remove_file(&object)?;
+            store::prune_empty_dirs(&dir, &object);
+        }
+    }
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+pub fn revisions(script: &store::Script) -> Vec<Revision> {
+    load_index(&script.root, &script.name).revisions
+}
+
+pub fn read_revision(script: &store::Script, revision: &Revision) -> io::Result<Vec<u8>> {
+    fs::read(object_path(&script.root, &revision.hash))
+}
+
+/// Saves the script's current content as a new revision, unless it is
+/// identical to the latest one. Scripts in read-only layers are not tracked.
+pub fn record(script: &store::Script, action: &str) -> io::Result<()> {
+    if script.layer.is_read_only() {
+        return Ok(());
+    }
+    let content = fs::read(&script.path)?;
+    let hash = sha256_hex(&content);
+
+    let mut index = load_index(&script.root, &script.name);
+    if index.revisions.last().is_some_and(|latest| latest.hash == hash) {
+        return Ok(());
+    }
+
+    let object = object_path(&script.root, &hash);
+    if !object.exists() {
+        fs::create_dir_all(object.parent().unwrap())?;
+        fs::write(&object, &content)?;
+    }
+    index.revisions.push(Revision { hash, saved_at: Local::now(), action: action.to_string() });
+    save_index(&script.root, &script.name, &index)
+}
+
+/// Like [`record`], but only warns on failure; history must never block an edit.
+pub fn record_or_warn(script: &store::Script, action: &str) {
+    if let Err(err) = record(script, action) {
+        eprintln!("Warning: could not save history for '{}': {}", script.name, err);
+    }
+}
+
+pub fn diff_stats(old: &str, new: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    let mut added = 0;
+    let mut removed = 0;
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    (added, removed)
+}
+
+pub fn history_command(args: &[String]) {
+    let name = match args {
+        [name] => name,
+        [flag, name] | [name, flag] if flag == "--json" => {
+            json::enable();
+            name
+        }
+        _ => json::fail("usage", "Usage: fastbash history <script> [--json]"),
+    };
+    let script = resolve_or_exit(name);
+    let revisions = revisions(&script);
+    if revisions.is_empty() && !json::enabled() {
+        println!("No history for '{}' yet", name);
+        return;
+    }
+
+    let mut previous = String::new();
+    let mut objects = Vec::new();
+    if !json::enabled() {
+        println!("{:<5} {:<17} {:<14} CHANGES", "REV", "SAVED", "ACTION");
+    }
+    for (i, revision) in revisions.iter().enumerate() {
+        let content = read_revision(&script, revision)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        let (added, removed) = diff_stats(&previous, &content);
+        previous = content;
+        if json::enabled() {
+            objects.push(json!({
+                "rev": i + 1,
+                "saved_at": revision.saved_at,
+                "action": revision.action,
+                "sha256": revision.hash,
+                "added": added,
+                "removed": removed,
+            }));
+            continue;
+        }
+        println!(
+            "{:<5} {:<17} {:<14} +{} -{}",
+            i + 1,
+            revision.saved_at.format("%Y-%m-%d %H:%M"),
+            revision.action,
+            added,
+            removed
+        );
+    }
+    if json::enabled() {
+        json::print(&objects.into());
+    }
+}
+
+/// Splits `name@rev` into the script name and a revision number.
+pub fn parse_revision_spec(spec: &str) -> (&str, Option<usize>) {
+    if let Some((name, rev)) = spec.rsplit_once('@')
+        && let Ok(rev) = rev.parse()
+    {
+        return (name, Some(rev));
+    }
+    (spec, None)
+}
+
+/// Content of revision `rev` (1-based), exiting with an error if it doesn't exist.
+pub fn revision_content(script: &store::Script, rev: usize) -> Vec<u8> {
+    let revisions = revisions(script);
+    let Some(revision) = rev.checked_sub(1).and_then(|i| revisions.get(i)) else {
+        let message = format!(
+            "Script '{}' has no revision {} (it has {}); see `fastbash history {}`",
+            script.name,
+            rev,
+            revisions.len(),
+            script.name
+        );
+        json::fail("not-found", message);
+    };
+    read_revision(script, revision).unwrap_or_else(|err| {
+        json::fail("io", format!("Failed to read revision {} of '{}': {}", rev, script.name, err))
+    })
+}
+
+/// `revert <script> [rev]` restores a revision, by default the one before the latest.
+pub fn revert_command(args: &[String]) {
+    let (name, rev) = match args {
+        [name] => (name, None),
+        [name, rev] => match rev.parse::<usize>() {
+            Ok(rev) => (name, Some(rev)),
+            Err(_) => json::fail("invalid-argument", format!("Invalid revision '{}'", rev)),
+        },
+        _ => json::fail("usage", "Usage: fastbash revert <script> [rev]"),
+    };
+    let script = resolve_or_exit(name);
+    if script.layer.is_read_only() {
+        json::fail("read-only", format!("Script '{}' is in the read-only {} store", name, script.layer));
+    }
+
+    // Keep whatever is on disk now, in case it was changed outside fastbash
+    record_or_warn(&script, "external");
+    let count = revisions(&script).len();
+    let rev = match rev {
+        Some(rev) => rev,
+        None if count >= 2 => count - 1,
+        None => json::fail("not-found", format!("Script '{}' has no earlier revision to revert to", name)),
+    };
+
+    let content = revision_content(&script, rev);
+    fs::write(&script.path, content).expect("Failed to write script");
+    make_executable(&script.path);
+    record_or_warn(&script, &format!("revert to {}", rev));
+    git::commit_script(&script, "revert");
+    json::done(
+        json!({ "action": "revert", "name": script.name, "path": script.path, "revision": rev }),
+        format_args!("Reverted '{}' to revision {}", name, rev),
+    );
+}
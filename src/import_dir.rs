@@ -0,0 +1,190 @@
+use std::{
+    fs,
+    io::Read,
+    os::unix::fs::{symlink, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use crate::{config::expand_home, confirm, git, history, json, make_executable, meta, store, COMMANDS};
+
+/// How much of a file is inspected to tell text from binary.
+const SNIFF_BYTES: usize = 8192;
+
+/// A file found in the source directory and what will happen to it.
+struct Candidate {
+    name: String,
+    source: PathBuf,
+    plan: Plan,
+}
+
+enum Plan {
+    Import { description: Option<String>, from_comment: bool },
+    Clash(store::LayerKind),
+    Skip(&'static str),
+}
+
+/// `import-dir <dir> [--link] [--dry-run] [--yes]` adopts the executable
+/// scripts in a directory such as `~/bin`, copying them into the store or
+/// (with `--link`) symlinking them so the originals stay in place.
+pub fn import_dir_command(args: &[String]) {
+    let usage = "Usage: fastbash import-dir <dir> [--link] [--dry-run] [--yes]";
+    let mut dir = None;
+    let mut link = false;
+    let mut dry_run = false;
+    let mut yes = false;
+    for arg in args {
+        match arg.as_str() {
+            "--link" | "-l" => link = true,
+            "--dry-run" | "-n" => dry_run = true,
+            "--yes" | "-y" => yes = true,
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(expand_home(arg)),
+            _ => json::fail("usage", usage),
+        }
+    }
+    let Some(dir) = dir else {
+        json::fail("usage", usage);
+    };
+    let dir = fs::canonicalize(&dir)
+        .unwrap_or_else(|err| json::fail("io", format!("Cannot read directory {}: {}", dir.display(), err)));
+
+    let candidates = scan(&dir);
+    if candidates.is_empty() {
+        println!("No files found in {}", dir.display());
+        return;
+    }
+    let verb = if link { "link" } else { "copy" };
+    let mut importable = 0;
+    for candidate in &candidates {
+        match &candidate.plan {
+            Plan::Import { description, from_comment } => {
+                importable += 1;
+                // Linked files are left untouched, so a leading comment can't become a header
+                let note = match (description, from_comment) {
+                    (Some(_), true) if link => String::new(),
+                    (Some(description), true) => format!("{} (from leading comment)", description),
+                    (Some(description), false) => description.lines().next().unwrap_or("").to_string(),
+                    (None, _) => String::new(),
+                };
+                println!("{:<6} {:<24} {}", verb, candidate.name, note);
+            }
+            Plan::Clash(layer) => {
+                println!("{:<6} {:<24} name taken by a script in the {} store", "clash", candidate.name, layer)
+            }
+            Plan::Skip(reason) => println!("{:<6} {:<24} {}", "skip", candidate.name, reason),
+        }
+    }
+
+    if importable == 0 {
+        println!("Nothing to import");
+        return;
+    }
+    if dry_run {
+        return;
+    }
+    if !yes && !confirm(&format!("{} {} script(s) into your store?", capitalize(verb), importable)) {
+        return;
+    }
+
+    let root = store::ensure_scripts_dir();
+    let mut imported = Vec::new();
+    for candidate in &candidates {
+        let Plan::Import { description, from_comment } = &candidate.plan else {
+            continue;
+        };
+        let target = root.join(&candidate.name);
+        let result = if link {
+            symlink(&candidate.source, &target)
+        } else {
+            copy_with_description(&candidate.source, &target, description.as_deref().filter(|_| *from_comment))
+        };
+        if let Err(err) = result {
+            eprintln!("Failed to import '{}': {}", candidate.name, err);
+            continue;
+        }
+        history::record_or_warn(&store::user_script(&candidate.name), "import");
+        imported.push(candidate.name.as_str());
+    }
+    if !imported.is_empty() {
+        git::auto_commit(&root, &imported, &format!("import-dir {}", dir.display()));
+    }
+    println!("Imported {} script(s) from {}", imported.len(), dir.display());
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Copies a script, writing a description taken from its leading comment
+/// into the header so fastbash can show it.
+fn copy_with_description(source: &Path, target: &PathBuf, description: Option<&str>) -> std::io::Result<()> {
+    let content = fs::read_to_string(source)?;
+    let content = match description {
+        Some(description) => meta::set_header_field(&content, "description", Some(description)),
+        None => content,
+    };
+    fs::write(target, content)?;
+    make_executable(target);
+    Ok(())
+}
+
+/// Files directly inside `dir`, sorted by name, each with its import plan.
+fn scan(dir: &Path) -> Vec<Candidate> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<Candidate> = entries
+        .flatten()
+        .filter(|entry| !store::is_hidden(&entry.path()))
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let source = entry.path();
+            let plan = plan(&name, &source);
+            Candidate { name, source, plan }
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    candidates
+}
+
+fn plan(name: &str, path: &Path) -> Plan {
+    let executable = fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o111 != 0);
+    if !executable {
+        return Plan::Skip("not executable");
+    }
+    let Ok(mut file) = fs::File::open(path) else {
+        return Plan::Skip("unreadable");
+    };
+    let mut head = Vec::new();
+    if file.by_ref().take(SNIFF_BYTES as u64).read_to_end(&mut head).is_err() {
+        return Plan::Skip("unreadable");
+    }
+    // A cut-off multi-byte character at the end of the sample is fine
+    let text = match std::str::from_utf8(&head) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => std::str::from_utf8(&head[..err.valid_up_to()]).unwrap(),
+        Err(_) => return Plan::Skip("binary file"),
+    };
+    if head.contains(&0) {
+        return Plan::Skip("binary file");
+    }
+    if !text.starts_with("#!") {
+        return Plan::Skip("no shebang");
+    }
+    if store::validate_name(name).is_err() || COMMANDS.contains(&name) {
+        return Plan::Skip("name cannot be used for a script");
+    }
+    if let Some(existing) = store::resolve(name) {
+        return Plan::Clash(existing.layer);
+    }
+    if store::get_scripts_dir().join(name).exists() {
+        return Plan::Skip("name taken by a script group");
+    }
+
+    let meta = meta::ScriptMeta::parse(text.lines());
+    match meta.description {
+        Some(description) => Plan::Import { description: Some(description), from_comment: false },
+        None => Plan::Import { description: meta::leading_comment(text), from_comment: true },
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    fmt, fs,
+    os::unix::fs::PermissionsExt,
+    process::exit,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::{DateTime, Local};
+use serde_json::{json, Value};
+
+use crate::{meta::ScriptMeta, runlog::RunRecord, stats, store, term};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Set by the global `--json` flag, or by a command's own `--json`.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The JSON shape of a script, shared by every command with `--json` output.
+pub fn script_object(script: &store::Script, meta: &ScriptMeta) -> Value {
+    json!({
+        "name": script.name,
+        "path": script.path,
+        "store": script.layer.to_string(),
+        "shadows": script.shadows.iter().map(|layer| layer.to_string()).collect::<Vec<_>>(),
+        "shebang": meta.shebang,
+        "interpreter": meta.interpreter(),
+        "description": meta.description,
+        "usage": meta.usage,
+        "tags": meta.tags,
+        "author": meta.author,
+        "args": meta.args,
+        "env": meta.env,
+        "requires": meta.requires,
+        "timeout": meta.timeout.map(|timeout| timeout.as_secs()),
+        "cwd": meta.cwd,
+    })
+}
+
+/// [`script_object`] plus the file's size, modification time and execute bit
+/// and how often it was run, as `ls` and `info` print it.
+pub fn script_details(script: &store::Script, meta: &ScriptMeta, usage: Option<&stats::Usage>) -> Value {
+    let metadata = fs::metadata(&script.path).ok();
+    let modified: Option<DateTime<Local>> =
+        metadata.as_ref().and_then(|m| m.modified().ok()).map(DateTime::from);
+    let mut object = script_object(script, meta);
+    object["size"] = json!(metadata.as_ref().map(|m| m.len()));
+    object["modified"] = json!(modified);
+    object["executable"] = json!(metadata.is_some_and(|m| m.permissions().mode() & 0o100 != 0));
+    object["runs"] = json!(usage.map_or(0, |usage| usage.runs));
+    object["failures"] = json!(usage.map_or(0, |usage| usage.failures));
+    object["last_run"] = json!(usage.map(|usage| usage.last_run));
+    object
+}
+
+/// A logged run. Unlike the log file itself, every key is always present.
+pub fn run_object(run: &RunRecord) -> Value {
+    json!({
+        "started_at": run.started_at,
+        "script": run.script,
+        "args": run.args,
+        "cwd": run.cwd,
+        "status": run.status(),
+        "exit_code": run.exit_code,
+        "signal": run.signal,
+        "timed_out": run.timed_out,
+        "error": run.error,
+        "duration_ms": run.duration_ms,
+    })
+}
+
+pub fn print(value: &Value) {
+    let mut out = serde_json::to_string_pretty(value).expect("Failed to serialize JSON");
+    out.push('\n');
+    term::print(&out);
+}
+
+/// Confirms a change: prints `object` (which names the `action`) in JSON
+/// mode, or the `text` line otherwise.
+pub fn done(object: Value, text: impl fmt::Display) {
+    if enabled() {
+        print(&object);
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// `{"error": {"code": ..., "message": ...}}`, the shape of every error in JSON mode.
+pub fn error_object(code: &str, message: impl fmt::Display) -> Value {
+    json!({ "error": { "code": code, "message": message.to_string() } })
+}
+
+/// Prints an error to stderr, as one line of JSON in JSON mode.
+pub fn report(code: &str, message: impl fmt::Display) {
+    if enabled() {
+        eprintln!("{}", error_object(code, message));
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// [`report`]s an error and exits 1.
+pub fn fail(code: &str, message: impl fmt::Display) -> ! {
+    report(code, message);
+    exit(1);
+}
@@ -0,0 +1,323 @@
+use std::{
+    fmt, fs,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::{exit, Command},
+    sync::LazyLock,
+};
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{find_in_path, json, meta::{self, ScriptMeta}, resolve_or_exit, store, term};
+
+/// `line 3: ` (bash) or `3: ` (dash) left at the start of a message once the path is removed.
+static LINE_PREFIX_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:line )?\d+:\s*").unwrap());
+
+/// Shells shellcheck understands, by interpreter name.
+const SHELLCHECK_SHELLS: &[&str] = &["sh", "bash", "dash", "ksh"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Style,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Style => "style",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One problem found in a script, either by a built-in check or by shellcheck.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    /// `missing-shebang`, `crlf`, ... for built-in checks, `SC2086` etc. for shellcheck.
+    pub code: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, code: &str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { line: None, column: None, severity, code: code.to_string(), message: message.into() }
+    }
+
+    fn at(mut self, line: usize) -> Diagnostic {
+        self.line = Some(line);
+        self
+    }
+}
+
+/// The fields of shellcheck's `-f json` output that we use.
+#[derive(Deserialize)]
+struct ShellcheckComment {
+    line: usize,
+    column: usize,
+    level: String,
+    code: u32,
+    message: String,
+}
+
+/// Runs the built-in checks on the script at `path`.
+pub fn builtin_checks(path: &Path) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let content = match fs::read(path) {
+        Ok(content) => String::from_utf8_lossy(&content).into_owned(),
+        Err(err) => return vec![Diagnostic::new(Severity::Error, "unreadable", err.to_string())],
+    };
+
+    let meta = ScriptMeta::parse(content.lines());
+    match &meta.shebang {
+        None => {
+            let message = "no shebang line (e.g. #!/bin/bash); the script cannot be run";
+            diagnostics.push(Diagnostic::new(Severity::Error, "missing-shebang", message).at(1));
+        }
+        Some(shebang) => diagnostics.extend(check_shebang(shebang, &meta)),
+    }
+
+    if let Some(i) = content.split('\n').position(|line| line.ends_with('\r')) {
+        let message = "Windows (CRLF) line endings; convert the file with `dos2unix`";
+        diagnostics.push(Diagnostic::new(Severity::Error, "crlf", message).at(i + 1));
+    }
+
+    let executable = fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o100 != 0);
+    if !executable {
+        diagnostics.push(Diagnostic::new(Severity::Error, "not-executable", "the file is not executable"));
+    }
+
+    if meta.description.is_none() {
+        let placeholder = content
+            .lines()
+            .take_while(|line| line.starts_with('#') || line.starts_with("//") || line.trim().is_empty())
+            .any(|line| line.contains("description:") && line.contains(meta::NO_DESCRIPTION));
+        let message = if placeholder {
+            format!("the description header is still the {} placeholder", meta::NO_DESCRIPTION)
+        } else {
+            format!("no `# description:` header; `ls` and `search` will show {}", meta::NO_DESCRIPTION)
+        };
+        diagnostics.push(Diagnostic::new(Severity::Warning, "missing-description", message));
+    }
+    diagnostics
+}
+
+fn check_shebang(shebang: &str, meta: &ScriptMeta) -> Vec<Diagnostic> {
+    let Some(program) = shebang.split_whitespace().next() else {
+        return vec![Diagnostic::new(Severity::Error, "invalid-shebang", "the shebang names no interpreter").at(1)];
+    };
+    if !program.starts_with('/') {
+        return vec![Diagnostic::new(
+            Severity::Error,
+            "invalid-shebang",
+            format!("the shebang must be an absolute path, not '{}' (try #!/usr/bin/env {})", program, program),
+        )
+        .at(1)];
+    }
+    if !find_in_path(program) {
+        let message = format!("'{}' does not exist", program);
+        return vec![Diagnostic::new(Severity::Error, "invalid-shebang", message).at(1)];
+    }
+    match meta.interpreter() {
+        Some(interpreter) if !find_in_path(interpreter) => vec![Diagnostic::new(
+            Severity::Error,
+            "interpreter-not-found",
+            format!("interpreter '{}' is not on PATH", interpreter),
+        )
+        .at(1)],
+        Some(_) => Vec::new(),
+        None => vec![Diagnostic::new(Severity::Error, "invalid-shebang", "`env` is given no interpreter").at(1)],
+    }
+}
+
+/// shellcheck's diagnostics for a shell script, or nothing when shellcheck
+/// isn't installed or the script is in another language.
+fn shellcheck(path: &Path, meta: &ScriptMeta) -> Vec<Diagnostic> {
+    if !meta.interpreter().is_some_and(|interpreter| SHELLCHECK_SHELLS.contains(&interpreter)) {
+        return Vec::new();
+    }
+    let Ok(output) = Command::new("shellcheck").args(["-f", "json"]).arg(path).output() else {
+        return Vec::new();
+    };
+    // shellcheck exits 1 when it finds something, so only the output matters
+    let comments: Vec<ShellcheckComment> = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    comments
+        .into_iter()
+        .map(|comment| Diagnostic {
+            line: Some(comment.line),
+            column: Some(comment.column),
+            severity: match comment.level.as_str() {
+                "error" => Severity::Error,
+                "warning" => Severity::Warning,
+                "info" => Severity::Info,
+                _ => Severity::Style,
+            },
+            code: format!("SC{}", comment.code),
+            message: comment.message,
+        })
+        .collect()
+}
+
+/// Runs the interpreter's own syntax check (`bash -n`, `node --check`, ...)
+/// without executing the script. Returns `None` when it passes or when the
+/// interpreter has no such check or isn't installed.
+pub fn syntax_check(path: &Path, meta: &ScriptMeta) -> Option<Diagnostic> {
+    let interpreter = meta.interpreter()?;
+    let language = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let mut cmd = Command::new(interpreter);
+    match language {
+        "sh" | "bash" | "dash" | "ksh" | "zsh" | "mksh" => cmd.arg("-n").arg(path),
+        // Compiling in-process keeps __pycache__ out of the store
+        "python" | "pypy" => cmd
+            .args(["-c", "import sys; compile(open(sys.argv[1]).read(), sys.argv[1], 'exec')"])
+            .arg(path),
+        "node" | "nodejs" => cmd.arg("--check").arg(path),
+        "ruby" | "perl" => cmd.arg("-c").arg(path),
+        _ => return None,
+    };
+    let output = cmd.output().ok()?;
+    if output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let path_text = path.to_string_lossy();
+    let lines: Vec<String> = stderr
+        .lines()
+        .map(|line| {
+            let line = line.replace(path_text.as_ref(), "");
+            line.trim_start_matches([':', ' ']).split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    // Python and node print a traceback or source excerpt before the error itself
+    let message = lines
+        .iter()
+        .find(|line| line.contains("rror"))
+        .or(lines.first())
+        .map(|line| LINE_PREFIX_RE.replace(line, "").into_owned())
+        .unwrap_or_else(|| format!("{} reported a syntax error", interpreter));
+    let mut diagnostic = Diagnostic::new(Severity::Error, "syntax-error", message);
+    // bash: `<path>: line 3:`, dash: `<path>: 3:`, python: `"<path>", line 3`,
+    // node and ruby: `<path>:3`, perl: `at <path> line 3.`
+    let pattern = format!(r#"{}(?:", line |: line | line |: |:)(\d+)"#, regex::escape(&path_text));
+    let location = Regex::new(&pattern).unwrap();
+    diagnostic.line = location.captures(&stderr).and_then(|caps| caps[1].parse().ok());
+    Some(diagnostic)
+}
+
+/// The problems worth stopping for right after an edit: a lost or broken
+/// shebang, CRLF line endings and syntax errors.
+pub fn post_edit_checks(path: &Path) -> Vec<Diagnostic> {
+    let meta = ScriptMeta::read(path);
+    let mut diagnostics: Vec<Diagnostic> =
+        builtin_checks(path).into_iter().filter(|d| d.severity == Severity::Error).collect();
+    diagnostics.extend(syntax_check(path, &meta));
+    diagnostics.sort_by_key(|d| d.line.unwrap_or(0));
+    diagnostics
+}
+
+/// All diagnostics for `script`, ordered by line.
+pub fn lint(script: &store::Script) -> Vec<Diagnostic> {
+    let meta = ScriptMeta::read(&script.path);
+    let mut diagnostics = builtin_checks(&script.path);
+    diagnostics.extend(syntax_check(&script.path, &meta));
+    diagnostics.extend(shellcheck(&script.path, &meta));
+    diagnostics.sort_by_key(|d| (d.line.unwrap_or(0), d.column.unwrap_or(0), d.severity));
+    diagnostics
+}
+
+/// `lint <script>... | --all [--strict] [--json]`. Exits 1 when an error was
+/// found, or with `--strict` on warnings too.
+pub fn lint_command(args: &[String]) {
+    let usage = "Usage: fastbash lint <script>... | --all [--strict] [--json]";
+    let mut names = Vec::new();
+    let mut all = false;
+    let mut strict = false;
+    for arg in args {
+        match arg.as_str() {
+            "--all" | "-a" => all = true,
+            "--strict" => strict = true,
+            "--json" => json::enable(),
+            _ if !arg.starts_with('-') => names.push(arg.as_str()),
+            _ => json::fail("usage", usage),
+        }
+    }
+    let scripts: Vec<store::Script> = match (all, names.is_empty()) {
+        (true, true) => store::all_scripts(),
+        (false, false) => names.into_iter().map(resolve_or_exit).collect(),
+        _ => json::fail("usage", usage),
+    };
+
+    let color = !json::enabled() && term::color_enabled();
+    let mut records = Vec::new();
+    let mut problems = 0;
+    let mut failing = 0;
+    let mut scripts_with_problems = 0;
+    for script in &scripts {
+        let diagnostics = lint(script);
+        if !diagnostics.is_empty() {
+            scripts_with_problems += 1;
+        }
+        problems += diagnostics.len();
+        failing += diagnostics.iter().filter(|d| strict || d.severity == Severity::Error).count();
+        for diagnostic in diagnostics {
+            if json::enabled() {
+                records.push(json!({
+                    "name": script.name,
+                    "path": script.path,
+                    "line": diagnostic.line,
+                    "column": diagnostic.column,
+                    "severity": diagnostic.severity.to_string(),
+                    "code": diagnostic.code,
+                    "message": diagnostic.message,
+                }));
+            } else {
+                println!("{}", format_diagnostic(&script.name, &diagnostic, color));
+            }
+        }
+    }
+
+    if json::enabled() {
+        json::print(&json!(records));
+    } else if problems == 0 {
+        println!("No problems found in {} script(s)", scripts.len());
+    } else {
+        println!("{} problem(s) in {} of {} script(s)", problems, scripts_with_problems, scripts.len());
+    }
+    if failing > 0 {
+        exit(1);
+    }
+}
+
+/// `name:line:col: severity[code]: message`, the format editors understand.
+pub fn format_diagnostic(name: &str, diagnostic: &Diagnostic, color: bool) -> String {
+    let mut location = name.to_string();
+    if let Some(line) = diagnostic.line {
+        location.push_str(&format!(":{}", line));
+        if let Some(column) = diagnostic.column {
+            location.push_str(&format!(":{}", column));
+        }
+    }
+    let severity = diagnostic.severity.to_string();
+    let severity = if color {
+        let paint = match diagnostic.severity {
+            Severity::Error => "\x1b[1;31m",
+            Severity::Warning => "\x1b[1;33m",
+            Severity::Info | Severity::Style => "\x1b[1;36m",
+        };
+        format!("{}{}\x1b[0m", paint, severity)
+    } else {
+        severity
+    };
+    format!("{}: {}[{}]: {}", location, severity, diagnostic.code, diagnostic.message)
+}
@@ -0,0 +1,344 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fs,
+    os::unix::fs::PermissionsExt,
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{alias, json, meta::ScriptMeta, stats, store, term};
+
+const SORT_KEYS: &[&str] = &["name", "mtime", "size", "runs", "frecency"];
+
+/// Name column bounds for the short listing.
+const MIN_NAME_WIDTH: usize = 16;
+const MAX_NAME_WIDTH: usize = 40;
+/// Descriptions are never cut shorter than this.
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+const BOLD_BLUE: &str = "\x1b[1;34m";
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Everything `ls` may show about one script.
+struct Row<'a> {
+    script: &'a store::Script,
+    meta: ScriptMeta,
+    size: u64,
+    modified: Option<DateTime<Local>>,
+    executable: bool,
+    runs: usize,
+    last_run: Option<DateTime<Local>>,
+}
+
+impl<'a> Row<'a> {
+    fn new(script: &'a store::Script, usage: &HashMap<String, stats::Usage>) -> Row<'a> {
+        let metadata = fs::metadata(&script.path).ok();
+        let usage = usage.get(&script.name);
+        Row {
+            script,
+            meta: ScriptMeta::read(&script.path),
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()).map(DateTime::from),
+            executable: metadata.is_some_and(|m| m.permissions().mode() & 0o100 != 0),
+            runs: usage.map_or(0, |usage| usage.runs),
+            last_run: usage.map(|usage| usage.last_run),
+        }
+    }
+}
+
+struct Options {
+    group: Option<String>,
+    tags: Vec<String>,
+    names_only: bool,
+    long: bool,
+    sort: String,
+}
+
+fn parse_options(args: &[String]) -> Options {
+    let usage = "Usage: fastbash ls [-l] [--names] [--tag <tag>]... [--sort name|mtime|size|runs|frecency] \
+                 [--json] [group]";
+    let mut options =
+        Options { group: None, tags: Vec::new(), names_only: false, long: false, sort: "name".to_string() };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--names" => options.names_only = true,
+            "-l" | "--long" => options.long = true,
+            "--json" => json::enable(),
+            "--tag" | "-t" => match iter.next() {
+                Some(tag) => options.tags.push(tag.to_lowercase()),
+                None => json::fail("usage", usage),
+            },
+            "--sort" | "-s" => match iter.next() {
+                Some(key) => options.sort = key.clone(),
+                None => json::fail("usage", usage),
+            },
+            _ if arg.starts_with("--sort=") => options.sort = arg["--sort=".len()..].to_string(),
+            _ if options.group.is_none() && !arg.starts_with('-') => {
+                options.group = Some(arg.trim_end_matches('/').to_string())
+            }
+            _ => json::fail("usage", usage),
+        }
+    }
+    if !SORT_KEYS.contains(&options.sort.as_str()) {
+        let message = format!("Unknown sort key '{}'; use one of {}", options.sort, SORT_KEYS.join(", "));
+        json::fail("invalid-argument", message);
+    }
+    options
+}
+
+pub fn list_command(args: &[String]) {
+    let options = parse_options(args);
+    let mut scripts = store::all_scripts();
+    if let Some(group) = &options.group {
+        let prefix = format!("{}/", group);
+        scripts.retain(|script| script.name.starts_with(&prefix));
+        if scripts.is_empty() {
+            json::fail("not-found", format!("Script group '{}' not found", group));
+        }
+    }
+
+    let usage = stats::usage();
+    let mut rows: Vec<Row> = scripts.iter().map(|script| Row::new(script, &usage)).collect();
+    rows.retain(|row| row.meta.has_tags(&options.tags));
+    if rows.is_empty() && !options.tags.is_empty() && !json::enabled() {
+        eprintln!("No scripts tagged {}", options.tags.join(", "));
+        return;
+    }
+    // all_scripts is in name order, and the sorts below are stable
+    match options.sort.as_str() {
+        "mtime" => rows.sort_by_key(|row| Reverse(row.modified)),
+        "size" => rows.sort_by_key(|row| Reverse(row.size)),
+        "runs" => rows.sort_by_key(|row| Reverse(row.runs)),
+        "frecency" => {
+            let score = |row: &Row| usage.get(&row.script.name).map_or(0.0, |usage| usage.frecency);
+            rows.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        }
+        _ => {}
+    }
+
+    if json::enabled() {
+        let objects: Vec<_> = rows
+            .iter()
+            .map(|row| json::script_details(row.script, &row.meta, usage.get(&row.script.name)))
+            .collect();
+        json::print(&objects.into());
+        return;
+    }
+    // An empty store lists nothing, not even the `-l` header
+    if rows.is_empty() {
+        return;
+    }
+    let lines = if options.names_only {
+        rows.iter().map(|row| row.script.name.clone()).collect()
+    } else if options.long {
+        long_listing(&rows)
+    } else if options.sort == "name" {
+        let strip = options.group.as_ref().map_or(0, |group| group.len() + 1);
+        tree_listing(&rows, strip)
+    } else {
+        // Grouping would break up the ranking, so other orders list full names
+        let entries = rows.iter().map(|row| (row.script.name.clone(), row)).collect();
+        short_listing(entries)
+    };
+    let mut out = lines.join("\n");
+    out.push('\n');
+    term::print(&out);
+}
+
+/// Groups as `name/` with their scripts indented below, scripts before subgroups.
+/// `strip` is the length of a group prefix that is not shown.
+fn tree_listing(rows: &[Row], strip: usize) -> Vec<String> {
+    let mut sorted: Vec<(Vec<&str>, &Row)> = rows
+        .iter()
+        .map(|row| (row.script.name[strip..].split('/').collect(), row))
+        .collect();
+    // Within a group, scripts sort before subgroups.
+    sorted.sort_by(|(a, _), (b, _)| {
+        let key = |parts: &Vec<&str>| -> Vec<(bool, String)> {
+            parts
+                .iter()
+                .enumerate()
+                .map(|(i, part)| (i + 1 < parts.len(), part.to_string()))
+                .collect()
+        };
+        key(a).cmp(&key(b))
+    });
+
+    // Group headings are `None`, placed before the scripts that follow them
+    let mut entries: Vec<(String, Option<&Row>)> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for (parts, row) in sorted {
+        let (leaf, groups) = parts.split_last().unwrap();
+        let common = current.iter().zip(groups).take_while(|(a, b)| a == b).count();
+        for (depth, group) in groups.iter().enumerate().skip(common) {
+            entries.push((format!("{}{}/", "  ".repeat(depth), group), None));
+        }
+        current = groups.to_vec();
+        entries.push((format!("{}{}", "  ".repeat(groups.len()), leaf), Some(row)));
+    }
+
+    let color = term::color_enabled();
+    let scripts: Vec<(String, &Row)> =
+        entries.iter().filter_map(|(name, row)| row.map(|row| (name.clone(), row))).collect();
+    let mut script_lines = short_listing(scripts).into_iter();
+    entries
+        .iter()
+        .map(|(name, row)| match row {
+            Some(_) => script_lines.next().unwrap_or_default(),
+            None => paint(color, BOLD_BLUE, name),
+        })
+        .collect()
+}
+
+/// `name  summary  [layer]  (alias: ...)` lines, with the name column sized
+/// to the longest name and descriptions cut to the terminal width.
+fn short_listing(entries: Vec<(String, &Row)>) -> Vec<String> {
+    let color = term::color_enabled();
+    let width = term::width();
+    let aliases = alias::by_target();
+    let name_width = entries
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp(MIN_NAME_WIDTH, MAX_NAME_WIDTH);
+
+    entries
+        .iter()
+        .map(|(name, row)| {
+            let notes = notes(row.script, &aliases);
+            let mut summary = row.meta.summary().to_string();
+            if let Some(width) = width {
+                let used = name_width.max(name.chars().count()) + 1 + notes.chars().count();
+                summary = truncate(&summary, width.saturating_sub(used).max(MIN_DESCRIPTION_WIDTH));
+            }
+            let name_color = if row.executable { GREEN } else { RED };
+            format!(
+                "{} {}{}",
+                paint(color, name_color, &pad(name, name_width)),
+                summary,
+                paint(color, DIM, &notes)
+            )
+        })
+        .collect()
+}
+
+/// A table of name, interpreter, size, modification time, last run,
+/// executable state, tags and description.
+fn long_listing(rows: &[Row]) -> Vec<String> {
+    let color = term::color_enabled();
+    let aliases = alias::by_target();
+    let header = ["NAME", "INTERP", "SIZE", "MODIFIED", "LAST RUN", "X", "TAGS"];
+    let table: Vec<[String; 7]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.script.name.clone(),
+                row.meta.interpreter().unwrap_or("-").to_string(),
+                human_size(row.size),
+                row.modified.map_or("-".to_string(), |time| time.format("%Y-%m-%d %H:%M").to_string()),
+                row.last_run.map_or("never".to_string(), |time| time.format("%Y-%m-%d %H:%M").to_string()),
+                if row.executable { "x" } else { "-" }.to_string(),
+                if row.meta.tags.is_empty() { "-".to_string() } else { row.meta.tags.join(",") },
+            ]
+        })
+        .collect();
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for cells in &table {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let fixed: usize = widths.iter().map(|width| width + 2).sum();
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    let titles: Vec<String> = header.iter().zip(&widths).map(|(title, width)| pad(title, *width)).collect();
+    lines.push(paint(color, DIM, &format!("{}  DESCRIPTION", titles.join("  "))));
+    for (row, cells) in rows.iter().zip(&table) {
+        let mut line = String::new();
+        for (i, (cell, width)) in cells.iter().zip(&widths).enumerate() {
+            // Sizes read best right-aligned
+            let text = if i == 2 { format!("{:>width$}", cell, width = width) } else { pad(cell, *width) };
+            let text = match i {
+                0 => paint(color, if row.executable { GREEN } else { RED }, &text),
+                5 if !row.executable => paint(color, RED, &text),
+                _ => text,
+            };
+            line.push_str(&text);
+            line.push_str("  ");
+        }
+        let notes = notes(row.script, &aliases);
+        let mut summary = row.meta.summary().to_string();
+        if let Some(width) = term::width() {
+            let available = width.saturating_sub(fixed + notes.chars().count());
+            summary = truncate(&summary, available.max(MIN_DESCRIPTION_WIDTH));
+        }
+        line.push_str(&summary);
+        line.push_str(&paint(color, DIM, &notes));
+        lines.push(line);
+    }
+    lines
+}
+
+/// `  [project; shadows user]  (alias: d)` for scripts outside the plain
+/// user store or with aliases.
+fn notes(script: &store::Script, aliases: &BTreeMap<String, Vec<String>>) -> String {
+    let mut notes = Vec::new();
+    if script.layer != store::LayerKind::User {
+        notes.push(script.layer.to_string());
+    }
+    if !script.shadows.is_empty() {
+        let shadowed: Vec<String> = script.shadows.iter().map(|layer| layer.to_string()).collect();
+        notes.push(format!("shadows {}", shadowed.join(", ")));
+    }
+    let mut out = String::new();
+    if !notes.is_empty() {
+        out.push_str(&format!("  [{}]", notes.join("; ")));
+    }
+    if let Some(names) = aliases.get(&script.name) {
+        out.push_str(&format!("  (alias: {})", names.join(", ")));
+    }
+    out
+}
+
+fn paint(color: bool, code: &str, text: &str) -> String {
+    if color && !text.is_empty() {
+        format!("{}{}{}", code, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!("{:<width$}", text, width = width)
+}
+
+/// Cuts `text` to `width` characters, ending with `…` when shortened.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "K", "M", "G"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[0])
+    } else {
+        format!("{:.1}{}", size, UNITS[unit])
+    }
+}
@@ -1,13 +1,48 @@
+mod alias;
+mod bundle;
+mod config;
+mod fuzzy;
+mod git;
+mod highlight;
+mod history;
+mod import_dir;
+mod json;
+mod lint;
+mod list;
+mod meta;
+mod picker;
+mod runlog;
+mod search;
+mod stats;
+mod store;
+mod suggest;
+mod tags;
+mod term;
+mod templates;
+mod trash;
+
 use std::{
     env,
-    fs::{self, File},
-    io::{self, BufRead, BufReader, Write},
-    os::unix::fs::PermissionsExt,
-    path::PathBuf,
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    os::unix::{fs::PermissionsExt, process::ExitStatusExt},
+    path::{Path, PathBuf},
     process::{exit, Command},
+    thread,
+    time::{Duration, Instant},
 };
 
-use regex::Regex;
+use config::expand_home;
+use meta::ScriptMeta;
+use store::get_scripts_dir;
+
+/// Subcommands dispatched by `main`. A script with one of these names could
+/// only be reached through `fastbash run`, so `create` refuses them.
+const COMMANDS: &[&str] = &[
+    "alias", "cat", "cp", "create", "edit", "export", "git", "grep", "help", "history", "import",
+    "import-dir", "info", "lint", "log", "ls", "mv", "restore", "revert", "rm", "run", "search", "show",
+    "stats", "sync", "tag", "tags", "templates", "trash",
+];
 
 fn print_help() {
     println!(
@@ -15,43 +50,166 @@ fn print_help() {
 fastbash — quick script manager
 
 USAGE:
-    fastbash create         # Create a new script interactively
+    fastbash [--scripts-dir <dir>] [--json] <command>
+    fastbash                # Pick a script interactively (on a terminal)
+
+COMMANDS:
+    fastbash create [name]  # Create a new script (prompts for a name if omitted)
+        --template <name>   #   start from a template
+        --from <file>       #   take the body from a file
+        --description <text>
+                            #   set the description header
+        --no-edit           #   don't open the editor
+        --force             #   overwrite an existing script
+    fastbash alias [ls]     # List script aliases
+    fastbash alias add <name> <script> [--args \"<args>\"]
+                            # Add a short name for a script, optionally with
+                            # arguments that are always passed first
+    fastbash alias rm <name>
+                            # Remove an alias
+    fastbash export [<script>...] [--tag <tag>] -o <bundle.tar.gz>
+                            # Bundle scripts (default: all) with a manifest
+    fastbash import <bundle.tar.gz>
+                            # Add a bundle's scripts to your store, asking
+                            # before replacing a script that differs
+    fastbash import-dir <dir> [--link] [--dry-run] [--yes]
+                            # Adopt the executable scripts in a directory
+                            # (e.g. ~/bin); --link symlinks instead of copying
+    fastbash git init       # Keep the script store in a git repository
+    fastbash git <args...>  # Run git inside the store, e.g. `git remote add`
+    fastbash sync [remote]  # Commit, merge from and push to a git remote
+    fastbash templates      # List templates (built-in: bash, sh, python, node)
+    fastbash templates add <name> [--from <file>]
+                            # Add a template to your templates directory
+    fastbash templates edit <name>
+                            # Edit a template (editing a built-in copies it)
     fastbash edit <script>  # Open script for editing
     fastbash <script> [...] # Run a saved script with optional args
-    fastbash ls             # List saved scripts
-    fastbash rm <script>    # Delete a saved script
+    fastbash run <script> [...]
+                            # Run a script, even one named like a command
+    fastbash ls [group]     # List saved scripts, optionally one group only
+    fastbash ls -l          # Also show interpreter, size, modification time,
+                            # last run, executable state and tags
+    fastbash ls --names     # Print bare script names, one per line
+    fastbash ls --tag <tag> # List only scripts with a tag (repeatable)
+    fastbash ls --sort name|mtime|size|runs|frecency
+                            # Sort the listing; frecency puts the scripts you
+                            # run most (and most recently) first
+    fastbash ls --json      # Scripts with metadata, size, mtime and run counts
+    fastbash lint <script>... | --all [--strict] [--json]
+                            # Check scripts for syntax errors, a missing or
+                            # broken shebang, CRLF line endings, a missing
+                            # execute bit or description, plus shellcheck
+                            # if installed. Exits 1 on errors; --strict
+                            # also on warnings
+    fastbash log [<script>] [--failed] [--since <when>] [--until <when>]
+                            # Show recent runs with exit status and duration
+                            # (when: 2h, 7d, 2024-05-01); -n <count>, --all,
+                            # --json
+    fastbash stats [-n <count>] [--all] [--json]
+                            # Most-used scripts with failure rates and
+                            # average durations
+    fastbash tag add <script> <tag>...
+                            # Add tags to a script's `# tags:` header
+    fastbash tag rm <script> <tag>...
+                            # Remove tags from a script
+    fastbash tags [--json]  # List all tags with the number of scripts using them
+    fastbash info <script> [--json]
+                            # Show a script's metadata header
+    fastbash search <query> [--tag <tag>] [--json]
+                            # Fuzzy-search script names, descriptions and tags
+    fastbash grep <regex> [-i] [--tag <tag>] [--json]
+                            # Search script bodies, printing script:line matches
+    fastbash show <script>[@rev]
+                            # Show a script (or a saved revision) with its
+                            # metadata and syntax highlighting; alias `cat`.
+                            # --raw prints only the file contents
+    fastbash history <script> [--json]
+                            # List saved revisions with change stats
+    fastbash revert <script> [rev]
+                            # Restore a revision (default: the previous one)
+    fastbash rm <script>    # Move a script to the trash
+    fastbash rm --force <script>
+                            # Delete a script permanently
+    fastbash mv <script> <new name>
+                            # Rename a script, keeping its history, run log
+                            # and aliases
+    fastbash cp <script> <new name>
+                            # Copy a script along with its history
+    fastbash trash [ls]     # List scripts in the trash
+    fastbash trash empty [--older-than <age>]
+                            # Permanently delete trashed scripts (age: 30d, 12h)
+    fastbash restore <script|id>
+                            # Restore a script from the trash
     fastbash help           # Show this help message
 
 NOTES:
-    - Scripts are saved in ~/.fastbash/scripts
+    - Scripts are saved in $XDG_DATA_HOME/fastbash/scripts
+      (~/.local/share/fastbash/scripts by default). Set FASTBASH_HOME, pass
+      --scripts-dir, or set `home`/`scripts_dir` in
+      ~/.config/fastbash/config.toml to use another location
+    - With --scripts-dir, templates and the run log are kept in `.data/`
+      inside that directory, and the store at the usual location is left
+      untouched
+    - An existing ~/.fastbash store is moved to the new location on first use
+    - Scripts are looked up in a project `.fastbash/` directory (nearest
+      parent of the current directory), then your store, then the read-only
+      system store /usr/share/fastbash/scripts (`system_dir` in the config)
+    - Scripts can be grouped in folders, e.g. `fastbash create db/backup`
+      and `fastbash db/backup`
+    - Script names may contain letters, digits and most punctuation, but no
+      `.`/`..` components, leading dashes or command names (ls, rm, ...)
+    - The picker lists your most frequently and recently run scripts first
+      and filters by name, description and tags as you type. Enter runs the
+      selected script, Ctrl-E edits it, Ctrl-D moves it to the trash
+    - Aliases are kept in the store's .config.toml. A script with the same
+      name as an alias takes precedence
+    - `import-dir` lists what it would do before asking. It skips binaries
+      and files without a shebang, reports names already in use, and turns a
+      leading comment into the description of copied scripts
     - Make sure your scripts start with a shebang line (e.g., #!/bin/bash)
-    - Set the EDITOR env variable to control which editor is used
+    - When a script isn't found, similar names are suggested. Set
+      `suggestions = \"auto\"` in the config to also run a script when the
+      name given is a prefix of exactly one script, or \"off\" to disable
+    - Set the EDITOR env variable to control which editor is used, and PAGER
+      for the pager used by `show`. NO_COLOR turns off colors
+    - `create` reads the script body from stdin when it is piped, e.g.
+      `history | tail -1 | fastbash create foo`
+    - `--json` (before the command, or after ls, info, search, grep, log,
+      stats, tags, lint and history) prints JSON instead of text; the global
+      flag also covers every other command. `show` prints the script with
+      its `content`, and commands that change something (create, rm, mv, cp,
+      tag, alias, restore, ...) print an object naming the `action`. Only
+      the interactive edit and import-dir and the git wrappers stay text.
+      Errors go to stderr as an `error` object with a `code` (usage, invalid-name,
+      invalid-argument, not-found, already-exists, read-only, conflict,
+      command-failed, io, ...) and a `message`
+    - Every run is logged (time, args, cwd, exit status, duration) in
+      $XDG_DATA_HOME/fastbash/runs.jsonl (`.data/runs.jsonl` under
+      --scripts-dir), which is rotated at 1 MiB
+    - After the editor closes, the script is made executable and checked for
+      syntax errors (`bash -n`, `node --check`, ...), CRLF line endings and
+      a lost shebang; you can reopen the editor at the problem or keep it
+    - Every create and edit saves a revision of the script; identical
+      contents are stored only once
+    - Once the store is a git repository, create, edit, rm and revert commit
+      automatically
+    - Templates live in $XDG_DATA_HOME/fastbash/templates and may use the
+      placeholders {{name}}, {{date}} and {{author}}
+    - Describe scripts with a header of `# key: value` comments after the
+      shebang: description, usage, tags, author, args, env, requires,
+      timeout, cwd
 "
     );
 }
 
-fn extract_description(path: &PathBuf) -> String {
-    let re = Regex::new(r"(?i)^#\s*(description|desc)\s*:\s*(.+)$").unwrap();
-
-    if let Ok(file) = File::open(path) {
-        let reader = BufReader::new(file);
-        for line in reader.lines().flatten().take(5) {
-            if let Some(caps) = re.captures(&line) {
-                return caps.get(2).map_or("(no description)".to_string(), |m| m.as_str().trim().to_string());
-            }
-        }
-    }
-    "(no description)".to_string()
-}
-
-fn get_scripts_dir() -> PathBuf {
-    let mut dir = dirs::home_dir().expect("Could not determine home directory");
-    dir.push(".fastbash/scripts");
-    fs::create_dir_all(&dir).expect("Failed to create script directory");
-    dir
+fn open_in_editor(path: &PathBuf) {
+    open_in_editor_at(path, None);
 }
 
-fn open_in_editor(path: &PathBuf) {
+/// Opens the editor with the cursor on `line`, using the flag the editor
+/// understands (`+N` for vi, nano, emacs and most others).
+fn open_in_editor_at(path: &PathBuf, line: Option<usize>) {
     let editor_string = env::var("EDITOR").ok()
         .unwrap_or_else(|| "nano".to_string());
 
@@ -65,14 +223,24 @@ fn open_in_editor(path: &PathBuf) {
     for arg in &editor_args {
         cmd.arg(arg);
     }
-    cmd.arg(path);
+    let editor_name = Path::new(editor_bin).file_name().map(|name| name.to_string_lossy().into_owned());
+    match (line, editor_name.as_deref()) {
+        (None, _) => {
+            cmd.arg(path);
+        }
+        (Some(line), Some("code" | "codium" | "code-insiders")) => {
+            cmd.arg("--goto").arg(format!("{}:{}", path.display(), line));
+        }
+        (Some(line), Some("hx" | "helix" | "subl" | "zed")) => {
+            cmd.arg(format!("{}:{}", path.display(), line));
+        }
+        (Some(line), _) => {
+            cmd.arg(format!("+{}", line)).arg(path);
+        }
+    }
 
-    let full_command = format!(
-        "{} {} {}",
-        editor_bin,
-        editor_args.join(" "),
-        path.to_string_lossy()
-    );
+    let args: Vec<String> = cmd.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let full_command = format!("{} {}", editor_bin, args.join(" "));
 
     // Try to run it
     match cmd.status() {
@@ -82,9 +250,90 @@ fn open_in_editor(path: &PathBuf) {
             }
         }
         Err(err) => {
-            eprintln!("Failed to run editor command: {}\nError: {}", full_command, err);
-            std::process::exit(1);
+            json::fail("command-failed", format!("Failed to run editor command: {}\nError: {}", full_command, err))
+        }
+    }
+}
+
+/// Opens a script in the editor, then makes it executable and checks it.
+/// While problems remain the user may reopen the editor at the first one
+/// or keep the file as it is.
+fn edit_and_validate(path: &PathBuf) {
+    let mut line = None;
+    loop {
+        open_in_editor_at(path, line);
+        make_executable(path);
+        let problems = lint::post_edit_checks(path);
+        let Some(first) = problems.first() else {
+            return;
+        };
+        let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        eprintln!("Problems found after editing:");
+        for problem in &problems {
+            eprintln!("    {}", lint::format_diagnostic(&name, problem, false));
+        }
+        if !io::stdin().is_terminal() || !confirm("Reopen the editor to fix it? (no keeps the file as is)") {
+            return;
         }
+        line = first.line;
+    }
+}
+
+/// Exits with an error unless `name` is a valid script name.
+fn check_name(name: &str) {
+    if let Err(err) = store::validate_name(name) {
+        json::fail("invalid-name", err);
+    }
+}
+
+/// Like [`check_name`], and also refuses names that start with a command.
+fn check_new_name(name: &str) {
+    check_name(name);
+    if let Some(err) = command_name_error(name) {
+        json::fail("invalid-name", err);
+    }
+}
+
+/// Why `name` can't be given to a new script, if it can't: it is invalid or
+/// starts with a command, so the script could only be reached through `run`.
+fn new_name_error(name: &str) -> Option<String> {
+    store::validate_name(name).err().or_else(|| command_name_error(name))
+}
+
+fn command_name_error(name: &str) -> Option<String> {
+    let first = name.split('/').next().unwrap_or_default();
+    COMMANDS
+        .contains(&first)
+        .then(|| format!("'{}' is a fastbash command and cannot be used as a script name", first))
+}
+
+/// Exits with a clear message when a script called `name` can't be written
+/// under `root`; see [`target_path_error`].
+fn check_target_path(root: &Path, name: &str) {
+    if let Some(err) = target_path_error(root, name) {
+        json::fail("already-exists", err);
+    }
+}
+
+/// Why a script called `name` can't be written under `root`, if it can't:
+/// one of its groups is already a script, or the name itself is a group.
+fn target_path_error(root: &Path, name: &str) -> Option<String> {
+    let parts: Vec<&str> = name.split('/').collect();
+    for end in 1..parts.len() {
+        let group = parts[..end].join("/");
+        if root.join(&group).is_file() {
+            return Some(format!("'{}' is a script, so it cannot be used as a script group", group));
+        }
+    }
+    root.join(name).is_dir().then(|| format!("'{}' is a script group; choose another name", name))
+}
+
+/// Validates and resolves `name`, exiting with "did you mean" hints if it doesn't exist.
+fn resolve_or_exit(name: &str) -> store::Script {
+    check_name(name);
+    match store::resolve(name) {
+        Some(script) => script,
+        None => suggest::not_found(name, config::get().suggestions != config::Suggestions::Off),
     }
 }
 
@@ -94,116 +343,630 @@ fn make_executable(path: &PathBuf) {
     fs::set_permissions(path, perms).unwrap();
 }
 
-fn create_script() {
-    print!("Enter script name: ");
-    io::stdout().flush().unwrap();
-    let mut name = String::new();
-    io::stdin().read_line(&mut name).unwrap();
-    let name = name.trim();
+fn create_script(args: &[String]) {
+    let usage = "Usage: fastbash create [<name>] [--template <name>] [--from <file>] \
+                 [--description <text>] [--no-edit] [--force]";
+    let mut name: Option<String> = None;
+    let mut template_name = templates::DEFAULT_TEMPLATE.to_string();
+    let mut from: Option<String> = None;
+    let mut description: Option<String> = None;
+    let mut no_edit = false;
+    let mut force = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next().cloned().unwrap_or_else(|| json::fail("usage", usage))
+        };
+        match arg.as_str() {
+            "--template" | "-t" => template_name = value(),
+            "--from" | "-f" => from = Some(value()),
+            "--description" | "-d" => description = Some(value()),
+            "--no-edit" => no_edit = true,
+            "--force" => force = true,
+            _ if name.is_none() && !arg.starts_with('-') => name = Some(arg.clone()),
+            _ => json::fail("usage", usage),
+        }
+    }
+
+    let interactive = io::stdin().is_terminal();
+    let name = match name {
+        Some(name) => name,
+        None if interactive => {
+            print!("Enter script name: ");
+            io::stdout().flush().unwrap();
+            let mut name = String::new();
+            io::stdin().read_line(&mut name).unwrap();
+            name.trim().to_string()
+        }
+        None => json::fail("usage", usage),
+    };
 
-    let script_path = get_scripts_dir().join(name);
+    check_new_name(&name);
+    check_target_path(&store::ensure_scripts_dir(), &name);
 
-    // If file doesn't exist yet, write default shebang and description placeholder
-    if !script_path.exists() {
-        fs::write(&script_path, "#!/bin/bash\n# description: (no description)\n").expect("Failed to write initial script");
+    let script_path = store::ensure_scripts_dir().join(&name);
+    if script_path.exists() && !force {
+        json::fail("already-exists", format!("Script '{}' already exists; use --force to overwrite it", name));
     }
+    let Some(template) = templates::load(&template_name) else {
+        json::fail("not-found", format!("Template '{}' not found; see `fastbash templates`", template_name));
+    };
 
-    open_in_editor(&script_path);
+    // The body comes from --from, then piped stdin, then the template
+    let body = match &from {
+        Some(file) => Some(
+            fs::read_to_string(file)
+                .unwrap_or_else(|err| json::fail("io", format!("Failed to read '{}': {}", file, err))),
+        ),
+        None if !interactive => {
+            let mut body = String::new();
+            io::stdin().read_to_string(&mut body).expect("Failed to read script from stdin");
+            Some(body).filter(|body| !body.trim().is_empty())
+        }
+        None => None,
+    };
+
+    let mut content = match body {
+        Some(body) if body.starts_with("#!") => body,
+        // A bare command gets the template's shebang line
+        Some(body) => {
+            let shebang = template
+                .lines()
+                .next()
+                .filter(|line| line.starts_with("#!"))
+                .unwrap_or("#!/bin/bash");
+            format!("{}\n{}", shebang, body)
+        }
+        None => templates::render(&template, &name),
+    };
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if let Some(description) = &description {
+        content = meta::set_header_field(&content, "description", Some(description));
+    }
+
+    let written = match script_path.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::write(&script_path, content)),
+        None => fs::write(&script_path, content),
+    };
+    if let Err(err) = written {
+        json::fail("io", format!("Failed to create script '{}': {}", name, err));
+    }
+
+    if !no_edit && interactive {
+        edit_and_validate(&script_path);
+    }
     make_executable(&script_path);
-    println!("Script '{}' created at {:?}", name, script_path);
-}
-
-fn list_scripts() {
-    let dir = get_scripts_dir();
-    for entry in fs::read_dir(dir).unwrap() {
-        let entry = entry.unwrap();
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(name) = path.file_name() {
-                let description = extract_description(&path);
-                println!("{:<20} {}", name.to_string_lossy(), description);
-            }
+    let script = store::user_script(&name);
+    history::record_or_warn(&script, "create");
+    git::commit_script(&script, "create");
+    json::done(
+        serde_json::json!({ "action": "create", "name": name, "path": script_path }),
+        format_args!("Script '{}' created at {:?}", name, script_path),
+    );
+}
+
+fn confirm(prompt: &str) -> bool {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).unwrap();
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn remove_script(args: &[String]) {
+    let (name, force) = match args {
+        [name] => (name.as_str(), false),
+        [flag, name] | [name, flag] if flag == "--force" || flag == "-f" => (name.as_str(), true),
+        _ => json::fail("usage", "Usage: fastbash rm [--force] <script>"),
+    };
+    let script = resolve_or_exit(name);
+    if script.layer.is_read_only() {
+        let message =
+            format!("Script '{}' is in the read-only {} store and cannot be removed", name, script.layer);
+        json::fail("read-only", message);
+    }
+
+    if force {
+        if let Err(err) = fs::remove_file(&script.path) {
+            json::fail("io", format!("Failed to remove '{}': {}", name, err));
         }
+        store::prune_empty_dirs(&script.root, &script.path);
+        if let Err(err) = history::forget(&script.root, &script.name) {
+            eprintln!("Warning: could not remove the history of '{}': {}", name, err);
+        }
+        git::commit_script(&script, "rm");
+        json::done(
+            serde_json::json!({ "action": "rm", "name": name, "path": script.path, "trash_id": null }),
+            format_args!("Permanently removed script '{}'", name),
+        );
+        return;
     }
+    let id = trash::move_to_trash(&script)
+        .unwrap_or_else(|err| json::fail("io", format!("Failed to move '{}' to the trash: {}", name, err)));
+    store::prune_empty_dirs(&script.root, &script.path);
+    git::commit_script(&script, "rm");
+    json::done(
+        serde_json::json!({ "action": "rm", "name": name, "path": script.path, "trash_id": id }),
+        format_args!("Moved script '{}' to the trash (undo with `fastbash restore {}`)", name, name),
+    );
 }
 
-fn remove_script(name: &str) {
-    let path = get_scripts_dir().join(name);
-    if path.exists() {
-        fs::remove_file(&path).unwrap();
-        println!("Removed script '{}'", name);
-    } else {
-        eprintln!("Script '{}' not found", name);
+/// `mv <old> <new>` renames a script within its store, taking its history,
+/// logged runs and aliases along.
+fn move_script(args: &[String]) {
+    let [old, new] = args else {
+        json::fail("usage", "Usage: fastbash mv <script> <new name>");
+    };
+    let script = resolve_or_exit(old);
+    if script.layer.is_read_only() {
+        let message = format!("Script '{}' is in the read-only {} store and cannot be renamed", old, script.layer);
+        json::fail("read-only", message);
+    }
+    check_new_name(new);
+    let target = script.root.join(new);
+    if target.is_file() {
+        json::fail("already-exists", format!("Script '{}' already exists", new));
+    }
+    check_target_path(&script.root, new);
+
+    if let Some(parent) = target.parent()
+        && let Err(err) = fs::create_dir_all(parent)
+    {
+        json::fail("io", format!("Failed to rename '{}' to '{}': {}", old, new, err));
+    }
+    if let Err(err) = fs::rename(&script.path, &target) {
+        json::fail("io", format!("Failed to rename '{}' to '{}': {}", old, new, err));
+    }
+    make_executable(&target);
+    store::prune_empty_dirs(&script.root, &script.path);
+    if let Err(err) = history::transfer(&script.root, &script.name, new, false) {
+        eprintln!("Warning: could not move the history of '{}': {}", old, err);
+    }
+    if let Err(err) = runlog::rename(&script.name, new) {
+        eprintln!("Warning: could not update the run log of '{}': {}", old, err);
+    }
+
+    let mut changed = vec![script.name.as_str(), new.as_str()];
+    match alias::retarget(&script.name, new) {
+        Ok(true) if script.root == get_scripts_dir() => changed.push(alias::STORE_CONFIG),
+        Ok(_) => {}
+        Err(err) => eprintln!("Warning: could not update aliases of '{}': {}", old, err),
+    }
+    git::auto_commit(&script.root, &changed, &format!("mv {} {}", script.name, new));
+    json::done(
+        serde_json::json!({ "action": "mv", "from": script.name, "name": new, "path": script.root.join(new) }),
+        format_args!("Renamed '{}' to '{}'", old, new),
+    );
+
+    let callers = search::callers_of(&script.name);
+    if !callers.is_empty() {
+        eprintln!("Warning: these scripts still call '{}':", old);
+        for (caller, line) in callers {
+            eprintln!("    {}:{}", caller, line);
+        }
     }
 }
 
+/// `cp <src> <dst>` copies a script, with its history, into the same store
+/// (or into the user store when the source is read-only).
+fn copy_script(args: &[String]) {
+    let [source, dest] = args else {
+        json::fail("usage", "Usage: fastbash cp <script> <new name>");
+    };
+    let script = resolve_or_exit(source);
+    check_new_name(dest);
+    let root = if script.layer.is_read_only() { store::ensure_scripts_dir() } else { script.root.clone() };
+    let target = root.join(dest);
+    if target.is_file() {
+        json::fail("already-exists", format!("Script '{}' already exists", dest));
+    }
+    check_target_path(&root, dest);
+
+    if let Some(parent) = target.parent()
+        && let Err(err) = fs::create_dir_all(parent)
+    {
+        json::fail("io", format!("Failed to copy '{}' to '{}': {}", source, dest, err));
+    }
+    if let Err(err) = fs::copy(&script.path, &target) {
+        json::fail("io", format!("Failed to copy '{}' to '{}': {}", source, dest, err));
+    }
+    make_executable(&target);
+    let copy = store::Script { name: dest.clone(), path: target, root, layer: script.layer, shadows: Vec::new() };
+    if script.root == copy.root
+        && let Err(err) = history::transfer(&copy.root, &script.name, dest, true)
+    {
+        eprintln!("Warning: could not copy the history of '{}': {}", source, err);
+    }
+    history::record_or_warn(&copy, &format!("copy of {}", script.name));
+    git::commit_script(&copy, "cp");
+    json::done(
+        serde_json::json!({ "action": "cp", "from": script.name, "name": dest, "path": copy.path }),
+        format_args!("Copied '{}' to '{}'", source, dest),
+    );
+}
+
 fn edit_script(name: &str) {
-    let path = get_scripts_dir().join(name);
-    if path.exists() {
-        open_in_editor(&path);
+    let script = resolve_or_exit(name);
+    if !script.layer.is_read_only() {
+        history::record_or_warn(&script, "external");
+        edit_and_validate(&script.path);
+        history::record_or_warn(&script, "edit");
+        git::commit_script(&script, "edit");
+        return;
+    }
+
+    let copy = get_scripts_dir().join(name);
+    let prompt = format!(
+        "Script '{}' is in the read-only {} store. Copy it to your store and edit the copy?",
+        name, script.layer
+    );
+    if !confirm(&prompt) {
+        return;
+    }
+    let copied = match copy.parent() {
+        Some(parent) => fs::create_dir_all(parent).and_then(|_| fs::copy(&script.path, &copy)),
+        None => fs::copy(&script.path, &copy),
+    };
+    if let Err(err) = copied {
+        json::fail("io", format!("Failed to copy '{}' to your store: {}", name, err));
+    }
+    make_executable(&copy);
+    let copy = store::user_script(name);
+    history::record_or_warn(&copy, &format!("copy from {}", script.layer));
+    edit_and_validate(&copy.path);
+    history::record_or_warn(&copy, "edit");
+    git::commit_script(&copy, "edit");
+}
+
+/// `show <script>[@rev]` prints the parsed header, then the script (or one
+/// of its saved revisions) highlighted for its interpreter. `--raw` prints
+/// only the file contents.
+fn show_script(args: &[String]) {
+    let (spec, raw) = match args {
+        [spec] => (spec, false),
+        [flag, spec] | [spec, flag] if flag == "--raw" => (spec, true),
+        _ => json::fail("usage", "Usage: fastbash show [--raw] <script>[@rev]"),
+    };
+    // A script whose name really contains `@<digits>` wins over a revision lookup
+    let (name, rev) = match store::resolve(spec) {
+        Some(_) => (spec.as_str(), None),
+        None => history::parse_revision_spec(spec),
+    };
+    let script = resolve_or_exit(name);
+
+    let content = match rev {
+        Some(rev) => history::revision_content(&script, rev),
+        None => fs::read(&script.path)
+            .unwrap_or_else(|err| json::fail("io", format!("Failed to read '{}': {}", name, err))),
+    };
+    let content = String::from_utf8_lossy(&content);
+    if json::enabled() {
+        let mut object = json::script_object(&script, &ScriptMeta::parse(content.lines()));
+        object["revision"] = rev.into();
+        object["content"] = content.into();
+        json::print(&object);
+        return;
+    }
+    if raw {
+        io::stdout().write_all(content.as_bytes()).unwrap();
+        return;
+    }
+
+    let meta = ScriptMeta::parse(content.lines());
+    let mut out = format_info(&script, &meta);
+    if let Some(rev) = rev {
+        out.push_str(&format!("{:<12} {}\n", "revision:", rev));
+    }
+    out.push_str(&format!("{}\n", "─".repeat(40)));
+    if term::color_enabled() {
+        let language = highlight::Language::from_interpreter(meta.interpreter());
+        out.push_str(&highlight::highlight(&content, language));
     } else {
-        eprintln!("Script '{}' not found", name);
+        out.push_str(&content);
     }
+    term::page(&out);
+}
+
+fn show_info(args: &[String]) {
+    let usage = "Usage: fastbash info <script> [--json]";
+    let mut name = None;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json::enable(),
+            _ if name.is_none() && !arg.starts_with('-') => name = Some(arg.as_str()),
+            _ => json::fail("usage", usage),
+        }
+    }
+    let Some(name) = name else {
+        json::fail("usage", usage);
+    };
+    let script = resolve_or_exit(name);
+    let meta = ScriptMeta::read(&script.path);
+    if json::enabled() {
+        json::print(&json::script_details(&script, &meta, stats::usage().get(&script.name)));
+    } else {
+        print!("{}", format_info(&script, &meta));
+    }
+}
+
+/// The `info` listing: where a script lives plus every header field that is set.
+fn format_info(script: &store::Script, meta: &ScriptMeta) -> String {
+    let mut out = String::new();
+    let mut field = |label: &str, values: &[&str]| {
+        for (i, line) in values.iter().flat_map(|v| v.lines()).enumerate() {
+            out.push_str(&format!("{:<12} {}\n", if i == 0 { label } else { "" }, line));
+        }
+    };
+
+    field("name:", &[&script.name]);
+    field("path:", &[&script.path.to_string_lossy()]);
+    field("store:", &[&script.layer.to_string()]);
+    if !script.shadows.is_empty() {
+        let shadowed: Vec<String> = script.shadows.iter().map(|layer| layer.to_string()).collect();
+        field("shadows:", &[&shadowed.join(", ")]);
+    }
+    if let Some(shebang) = &meta.shebang {
+        field("shebang:", &[shebang]);
+    }
+    field("description:", &[meta.description_or_default()]);
+    if let Some(usage) = &meta.usage {
+        field("usage:", &[usage]);
+    }
+    if !meta.tags.is_empty() {
+        field("tags:", &[&meta.tags.join(", ")]);
+    }
+    if let Some(author) = &meta.author {
+        field("author:", &[author]);
+    }
+    let args: Vec<&str> = meta.args.iter().map(String::as_str).collect();
+    field("args:", &args);
+    let env: Vec<&str> = meta.env.iter().map(String::as_str).collect();
+    field("env:", &env);
+    if !meta.requires.is_empty() {
+        field("requires:", &[&meta.requires.join(", ")]);
+    }
+    if let Some(timeout) = meta.timeout {
+        field("timeout:", &[&meta::format_duration(timeout)]);
+    }
+    if let Some(cwd) = &meta.cwd {
+        field("cwd:", &[cwd]);
+    }
+    out
+}
+
+fn find_in_path(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
 }
 
 fn run_script(name: &str, args: &[String]) {
-    let path = get_scripts_dir().join(name);
-    if !path.exists() {
-        eprintln!("Script '{}' not found", name);
-        exit(1);
+    check_name(name);
+    let mode = config::get().suggestions;
+    let script = match store::resolve(name) {
+        Some(script) => script,
+        // Like git, an unambiguous abbreviation is as good as the full name
+        None if mode == config::Suggestions::Auto => {
+            let unique = suggest::unique_prefix_match(name, &suggest::visible_names());
+            match unique.and_then(|full| store::resolve(&full)) {
+                Some(script) => {
+                    eprintln!("Running '{}'", script.name);
+                    script
+                }
+                None => suggest::not_found(name, true),
+            }
+        }
+        None => suggest::not_found(name, mode != config::Suggestions::Off),
+    };
+    let path = script.path;
+
+    let meta = ScriptMeta::read(&path);
+
+    let missing: Vec<&str> = meta
+        .requires
+        .iter()
+        .map(String::as_str)
+        .filter(|program| !find_in_path(program))
+        .collect();
+    if !missing.is_empty() {
+        let message =
+            format!("Script '{}' requires commands that are not on PATH: {}", name, missing.join(", "));
+        json::fail("missing-command", message);
     }
 
-    let result = Command::new(&path)
-        .args(args)
-        .status();
+    let mut cmd = Command::new(&path);
+    cmd.args(args);
+    let cwd = match &meta.cwd {
+        Some(cwd) => expand_home(cwd),
+        None => env::current_dir().unwrap_or_default(),
+    };
+    cmd.current_dir(&cwd);
+    // `# env: NAME=default` supplies a default when the variable is unset
+    for entry in &meta.env {
+        let spec = entry.split_whitespace().next().unwrap_or("");
+        if let Some((key, default)) = spec.split_once('=')
+            && env::var_os(key).is_none()
+        {
+            cmd.env(key, default);
+        }
+    }
+
+    let started_at = chrono::Local::now();
+    let started = Instant::now();
+    let result = cmd.spawn().and_then(|mut child| match meta.timeout {
+        Some(timeout) => wait_with_timeout(&mut child, timeout),
+        None => child.wait().map(Some),
+    });
+    runlog::record_or_warn(&runlog::RunRecord {
+        started_at,
+        script: script.name.clone(),
+        args: args.to_vec(),
+        cwd,
+        exit_code: match &result {
+            Ok(Some(status)) => status.code(),
+            Ok(None) => Some(124),
+            Err(_) => None,
+        },
+        signal: match &result {
+            Ok(Some(status)) => status.signal(),
+            _ => None,
+        },
+        timed_out: matches!(result, Ok(None)),
+        error: result.as_ref().err().map(|err| err.to_string()),
+        duration_ms: started.elapsed().as_millis() as u64,
+    });
 
     match result {
-        Ok(status) => {
+        Ok(Some(status)) => {
             if !status.success() {
-                eprintln!("Script exited with non-zero status: {}", status);
+                json::report("script-failed", format!("Script exited with non-zero status: {}", status));
                 exit(status.code().unwrap_or(1));
             }
         }
+        Ok(None) => {
+            let timeout = meta::format_duration(meta.timeout.unwrap_or_default());
+            json::report("timeout", format!("Script '{}' timed out after {}", name, timeout));
+            exit(124);
+        }
         Err(err) => {
-            if let Some(8) = err.raw_os_error() {
-                eprintln!(
+            let message = if let Some(8) = err.raw_os_error() {
+                format!(
                     "Failed to execute '{}': Exec format error.\n\
-                     Hint: Make sure the script starts with a valid shebang line (e.g., #!/bin/bash)",
-                    name
-                );
+                     Hint: Make sure the script starts with a valid shebang line (e.g., #!/bin/bash); \
+                     `fastbash lint {}` checks for this and other problems",
+                    name, name
+                )
             } else {
-                eprintln!("Failed to run script '{}': {}", name, err);
+                format!("Failed to run script '{}': {}", name, err)
+            };
+            json::fail("command-failed", message);
+        }
+    }
+}
+
+/// Waits for the child, killing it once `timeout` elapses. Returns `None` on timeout.
+fn wait_with_timeout(
+    child: &mut std::process::Child,
+    timeout: Duration,
+) -> io::Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Running `fastbash` alone opens the picker on a terminal, and shows the
+/// help otherwise (or when there are no scripts to pick from).
+fn pick_script() {
+    let mut scripts = store::all_scripts();
+    stats::sort_by_frecency(&mut scripts);
+    if scripts.is_empty() || !picker::is_available() {
+        print_help();
+        return;
+    }
+    let action = picker::pick(scripts)
+        .unwrap_or_else(|err| json::fail("command-failed", format!("Failed to run the picker: {}", err)));
+    match action {
+        Some(picker::Action::Run(name)) => run_script(&name, &[]),
+        Some(picker::Action::Edit(name)) => edit_script(&name),
+        Some(picker::Action::Delete(name)) if confirm(&format!("Move script '{}' to the trash?", name)) => {
+            remove_script(&[name]);
+        }
+        Some(picker::Action::Delete(_)) | None => {}
+    }
+}
+
+/// Consumes global flags that appear before the subcommand.
+fn parse_global_flags(args: &mut Vec<String>) {
+    // A repeated --scripts-dir overrides the earlier one
+    let mut scripts_dir = None;
+    while let Some(first) = args.first() {
+        if first == "--scripts-dir" {
+            if args.len() < 2 {
+                json::fail("usage", "Usage: fastbash --scripts-dir <dir> <command>");
             }
-            exit(1);
+            scripts_dir = Some(expand_home(&args[1]));
+            args.drain(..2);
+        } else if let Some(dir) = first.strip_prefix("--scripts-dir=") {
+            scripts_dir = Some(expand_home(dir));
+            args.remove(0);
+        } else if first == "--json" {
+            json::enable();
+            args.remove(0);
+        } else {
+            break;
         }
     }
+    if let Some(dir) = scripts_dir {
+        store::set_scripts_dir_override(dir);
+    }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    parse_global_flags(&mut args);
 
     if args.is_empty() {
-        print_help();
+        pick_script();
     } else {
         match args[0].as_str() {
-            "ls" => list_scripts(),
-            "create" => create_script(),
+            "ls" => list::list_command(&args[1..]),
+            "create" => create_script(&args[1..]),
             "edit" => {
                 if args.len() < 2 {
-                    eprintln!("Usage: fastbash edit <script>");
-                    exit(1);
+                    json::fail("usage", "Usage: fastbash edit <script>");
                 }
                 edit_script(&args[1]);
             }
-            "rm" => {
+            "rm" => remove_script(&args[1..]),
+            "mv" => move_script(&args[1..]),
+            "cp" => copy_script(&args[1..]),
+            "show" | "cat" => show_script(&args[1..]),
+            "search" => search::search_command(&args[1..]),
+            "grep" => search::grep_command(&args[1..]),
+            "export" => bundle::export_command(&args[1..]),
+            "import" => bundle::import_command(&args[1..]),
+            "import-dir" => import_dir::import_dir_command(&args[1..]),
+            "git" => git::git_command(&args[1..]),
+            "sync" => git::sync_command(&args[1..]),
+            "history" => history::history_command(&args[1..]),
+            "revert" => history::revert_command(&args[1..]),
+            "trash" => trash::trash_command(&args[1..]),
+            "restore" => trash::restore_command(&args[1..]),
+            "lint" => lint::lint_command(&args[1..]),
+            "log" => runlog::log_command(&args[1..]),
+            "stats" => stats::stats_command(&args[1..]),
+            "tag" => tags::tag_command(&args[1..]),
+            "tags" => tags::tags_command(&args[1..]),
+            "info" => show_info(&args[1..]),
+            "run" => {
                 if args.len() < 2 {
-                    eprintln!("Usage: fastbash rm <script>");
-                    exit(1);
+                    json::fail("usage", "Usage: fastbash run <script> [args...]");
                 }
-                remove_script(&args[1]);
+                let (name, args) = alias::expand(&args[1], &args[2..]);
+                run_script(&name, &args);
             }
+            "templates" => templates::templates_command(&args[1..]),
             "help" | "--help" | "-h" => print_help(),
-            script_name => run_script(script_name, &args[1..]),
+            "alias" => alias::alias_command(&args[1..]),
+            script_name => {
+                let (name, args) = alias::expand(script_name, &args[1..]);
+                run_script(&name, &args);
+            }
         }
     }
 }
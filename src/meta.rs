@@ -0,0 +1,616 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use regex::Regex;
+
+/// Upper bound on how far into a file we look for the header block.
+const MAX_HEADER_LINES: usize = 200;
+
+pub const NO_DESCRIPTION: &str = "(no description)";
+
+const FIELD_KEYS: &str = "description|desc|usage|tags|author|args|env|requires|timeout|cwd";
+
+static FIELD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r"(?i)^({})\s*:\s*(.*)$", FIELD_KEYS)).unwrap());
+
+/// A raw header line: comment prefix, key, separator and value.
+static RAW_FIELD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)^(\s*(?:#|//)?\s*)({})(\s*:\s*)(.*?)(\r?\n)?$", FIELD_KEYS)).unwrap()
+});
+
+/// Metadata parsed from the comment header at the top of a script.
+///
+/// The header is a block of `key: value` lines, written either as `#`
+/// comments (bash, sh, python, ruby, ...), `//` comments (node) or inside
+/// a `"""` docstring.
+/// Indented lines continue the previous field, and list fields may be
+/// repeated.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptMeta {
+    pub shebang: Option<String>,
+    pub description: Option<String>,
+    pub usage: Option<String>,
+    pub tags: Vec<String>,
+    pub author: Option<String>,
+    pub args: Vec<String>,
+    pub env: Vec<String>,
+    pub requires: Vec<String>,
+    pub timeout: Option<Duration>,
+    pub cwd: Option<String>,
+}
+
+impl ScriptMeta {
+    pub fn read(path: &Path) -> ScriptMeta {
+        match File::open(path) {
+            Ok(file) => {
+                let lines = BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .take(MAX_HEADER_LINES);
+                Self::parse(lines)
+            }
+            Err(_) => ScriptMeta::default(),
+        }
+    }
+
+    pub fn parse<I, S>(lines: I) -> ScriptMeta
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut meta = ScriptMeta::default();
+        let mut parser = HeaderParser::default();
+        let mut docstring: Option<&'static str> = None;
+        let mut first = true;
+
+        for line in lines {
+            let line = line.as_ref().trim_end_matches('\r');
+
+            if first {
+                first = false;
+                if let Some(shebang) = line.strip_prefix("#!") {
+                    meta.shebang = Some(shebang.trim().to_string());
+                    continue;
+                }
+            }
+
+            if let Some(delim) = docstring {
+                match line.find(delim) {
+                    Some(end) => {
+                        parser.feed(&mut meta, &line[..end]);
+                        break;
+                    }
+                    None => parser.feed(&mut meta, line),
+                }
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            if let Some(delim) = ["\"\"\"", "'''"].into_iter().find(|d| trimmed.starts_with(d)) {
+                if parser.started {
+                    break;
+                }
+                let rest = &trimmed[delim.len()..];
+                if let Some(end) = rest.find(delim) {
+                    parser.feed(&mut meta, &rest[..end]);
+                    break;
+                }
+                parser.feed(&mut meta, rest);
+                docstring = Some(delim);
+                continue;
+            }
+
+            if let Some(comment) = trimmed.strip_prefix("//").or_else(|| trimmed.strip_prefix('#')) {
+                parser.feed(&mut meta, comment.strip_prefix(' ').unwrap_or(comment));
+            } else if trimmed.is_empty() && !parser.started {
+                continue;
+            } else {
+                break;
+            }
+        }
+
+        meta
+    }
+
+    /// Program named by the shebang, looking through `/usr/bin/env`:
+    /// `#!/usr/bin/env -S python3 -u` gives `python3`.
+    pub fn interpreter(&self) -> Option<&str> {
+        let mut words = self.shebang.as_deref()?.split_whitespace();
+        let program = words.next()?.rsplit('/').next()?;
+        if program != "env" {
+            return Some(program);
+        }
+        words.find(|word| !word.starts_with('-') && !word.contains('='))
+    }
+
+    pub fn description_or_default(&self) -> &str {
+        self.description.as_deref().unwrap_or(NO_DESCRIPTION)
+    }
+
+    /// First line of the description, for one-line listings.
+    pub fn summary(&self) -> &str {
+        self.description_or_default().lines().next().unwrap_or("")
+    }
+
+    /// Whether the script carries every one of `tags`, ignoring case.
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+#[derive(Default)]
+struct HeaderParser {
+    started: bool,
+    current: Option<Field>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Description,
+    Usage,
+    Tags,
+    Author,
+    Args,
+    Env,
+    Requires,
+    Timeout,
+    Cwd,
+}
+
+impl HeaderParser {
+    fn feed(&mut self, meta: &mut ScriptMeta, line: &str) {
+        if let Some(caps) = FIELD_RE.captures(line) {
+            let field = match caps[1].to_ascii_lowercase().as_str() {
+                "description" | "desc" => Field::Description,
+                "usage" => Field::Usage,
+                "tags" => Field::Tags,
+                "author" => Field::Author,
+                "args" => Field::Args,
+                "env" => Field::Env,
+                "requires" => Field::Requires,
+                "timeout" => Field::Timeout,
+                _ => Field::Cwd,
+            };
+            self.started = true;
+            self.current = Some(field);
+            apply(meta, field, caps[2].trim(), false);
+        } else if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            if let Some(field) = self.current {
+                apply(meta, field, line.trim(), true);
+            }
+        } else {
+            self.current = None;
+        }
+    }
+}
+
+fn apply(meta: &mut ScriptMeta, field: Field, value: &str, continuation: bool) {
+    match field {
+        // The placeholder written by `create` is not a real description
+        Field::Description if value == NO_DESCRIPTION => {}
+        Field::Description => append_text(&mut meta.description, value),
+        Field::Usage => append_text(&mut meta.usage, value),
+        Field::Author => match &mut meta.author {
+            Some(author) if !continuation => {
+                author.push_str(", ");
+                author.push_str(value);
+            }
+            Some(author) => {
+                author.push(' ');
+                author.push_str(value);
+            }
+            None => meta.author = Some(value.to_string()),
+        },
+        Field::Tags => meta.tags.extend(split_list(value)),
+        Field::Requires => meta.requires.extend(split_list(value)),
+        Field::Args => append_item(&mut meta.args, value, continuation),
+        Field::Env => append_item(&mut meta.env, value, continuation),
+        Field::Timeout => {
+            if let Some(timeout) = parse_duration(value) {
+                meta.timeout = Some(timeout);
+            }
+        }
+        Field::Cwd => {
+            if !value.is_empty() {
+                meta.cwd = Some(value.to_string());
+            }
+        }
+    }
+}
+
+fn append_text(slot: &mut Option<String>, value: &str) {
+    if value.is_empty() {
+        return;
+    }
+    match slot {
+        Some(text) => {
+            text.push('\n');
+            text.push_str(value);
+        }
+        None => *slot = Some(value.to_string()),
+    }
+}
+
+fn append_item(items: &mut Vec<String>, value: &str, continuation: bool) {
+    if value.is_empty() {
+        return;
+    }
+    match items.last_mut() {
+        Some(last) if continuation => {
+            last.push(' ');
+            last.push_str(value);
+        }
+        _ => items.push(value.to_string()),
+    }
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
+/// Rewrites one header field in a script's source, leaving everything else
+/// untouched. The first `key:` line is replaced and any repeats or
+/// continuation lines are dropped. With `value` of `None` the field is
+/// removed. A missing field is added after the last header field, or after
+/// the shebang when the script has no header yet.
+pub fn set_header_field(content: &str, key: &str, value: Option<&str>) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let header_end = header_end(&lines);
+    let same_key = |k: &str| {
+        let k = k.to_ascii_lowercase();
+        let normalize = |k: &str| if k == "desc" { "description".to_string() } else { k.to_string() };
+        normalize(&k) == normalize(&key.to_ascii_lowercase())
+    };
+
+    let mut out = String::with_capacity(content.len() + 32);
+    let mut replaced = false;
+    let mut skipping = false;
+    let mut last_field: Option<(usize, String)> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if i < header_end {
+            if let Some(caps) = RAW_FIELD_RE.captures(line) {
+                skipping = false;
+                if same_key(&caps[2]) {
+                    skipping = true;
+                    if !replaced
+                        && let Some(value) = value
+                    {
+                        out.push_str(&format!("{}{}{}{}\n", &caps[1], &caps[2], &caps[3], value));
+                    }
+                    replaced = true;
+                    continue;
+                }
+                last_field = Some((out.len() + line.len(), caps[1].to_string()));
+            } else if skipping && is_continuation(line) {
+                continue;
+            } else {
+                skipping = false;
+            }
+        }
+        out.push_str(line);
+        if !line.ends_with('\n') && i + 1 < lines.len() {
+            out.push('\n');
+        }
+    }
+
+    if !replaced && let Some(value) = value {
+        let (at, prefix) = match last_field {
+            Some(found) => found,
+            None => {
+                let shebang = lines.first().filter(|line| line.starts_with("#!"));
+                let prefix = match shebang {
+                    Some(line) if ["node", "deno", "bun"].iter().any(|js| line.contains(js)) => "// ",
+                    _ => "# ",
+                };
+                (shebang.map_or(0, |line| line.len()), prefix.to_string())
+            }
+        };
+        let mut insert = format!("{}{}: {}\n", prefix, key, value);
+        if at > 0 && !out[..at].ends_with('\n') {
+            insert.insert(0, '\n');
+        }
+        out.insert_str(at, &insert);
+    }
+    out
+}
+
+/// Index of the first line after the header block (shebang, comments and docstring).
+fn header_end(lines: &[&str]) -> usize {
+    let mut docstring: Option<&str> = None;
+    let mut seen_field = false;
+    for (i, line) in lines.iter().enumerate() {
+        if i == 0 && line.starts_with("#!") {
+            continue;
+        }
+        let trimmed = line.trim();
+        if let Some(delim) = docstring {
+            if trimmed.contains(delim) {
+                return i + 1;
+            }
+            continue;
+        }
+        if let Some(delim) = ["\"\"\"", "'''"].into_iter().find(|d| trimmed.starts_with(d)) {
+            if trimmed[delim.len()..].contains(delim) {
+                return i + 1;
+            }
+            docstring = Some(delim);
+        } else if trimmed.starts_with('#') || trimmed.starts_with("//") {
+            seen_field |= RAW_FIELD_RE.is_match(line);
+        } else if !trimmed.is_empty() || seen_field {
+            return i;
+        }
+    }
+    lines.len()
+}
+
+fn is_continuation(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let content = trimmed
+        .strip_prefix("//")
+        .or_else(|| trimmed.strip_prefix('#'))
+        .map(|rest| rest.strip_prefix(' ').unwrap_or(rest))
+        .unwrap_or(line);
+    content.starts_with([' ', '\t']) && !content.trim().is_empty()
+}
+
+/// Parses durations like `90`, `30s`, `5m`, `1h30m` or `7d`.
+/// A bare number is taken as seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: u64 = number.parse().ok()?;
+        number.clear();
+        total += n * match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(Duration::from_secs(total))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        return format!("{}ms", duration.as_millis());
+    }
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    let mut out = String::new();
+    if h > 0 {
+        out.push_str(&format!("{}h", h));
+    }
+    if m > 0 {
+        out.push_str(&format!("{}m", m));
+    }
+    if s > 0 || out.is_empty() {
+        out.push_str(&format!("{}s", s));
+    }
+    out
+}
+
+/// Free-form comment text at the top of a script that has no `description:`
+/// field, e.g. `# Back up the photo library to the NAS`. Returns the first
+/// paragraph of the leading comment block, skipping editor and linter
+/// directives.
+pub fn leading_comment(content: &str) -> Option<String> {
+    let mut paragraph: Vec<&str> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if i == 0 && trimmed.starts_with("#!") {
+            continue;
+        }
+        let Some(comment) = trimmed.strip_prefix("//").or_else(|| trimmed.strip_prefix('#')) else {
+            if trimmed.is_empty() && paragraph.is_empty() {
+                continue;
+            }
+            break;
+        };
+        let text = comment.trim_start_matches(['#', '/']).trim();
+        let directive = ["shellcheck", "vim:", "vi:", "-*-", "shellcheck:"]
+            .iter()
+            .any(|prefix| text.starts_with(prefix));
+        if text.is_empty() || directive || FIELD_RE.is_match(text) {
+            if paragraph.is_empty() {
+                continue;
+            }
+            break;
+        }
+        paragraph.push(text);
+    }
+    (!paragraph.is_empty()).then(|| paragraph.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> ScriptMeta {
+        ScriptMeta::parse(content.lines())
+    }
+
+    #[test]
+    fn parses_hash_header() {
+        let meta = parse(
+            "#!/usr/bin/env bash\n# description: Back up photos\n# tags: backup, nas\n# timeout: 5m\necho hi\n",
+        );
+        assert_eq!(meta.shebang.as_deref(), Some("/usr/bin/env bash"));
+        assert_eq!(meta.interpreter(), Some("bash"));
+        assert_eq!(meta.description.as_deref(), Some("Back up photos"));
+        assert_eq!(meta.tags, ["backup", "nas"]);
+        assert_eq!(meta.timeout, Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn continuation_lines_extend_the_description() {
+        let meta =
+            parse("#!/bin/bash\n# description: Deploy the app.\n#   Runs migrations first.\n# usage: deploy\n");
+        assert_eq!(meta.description.as_deref(), Some("Deploy the app.\nRuns migrations first."));
+        assert_eq!(meta.summary(), "Deploy the app.");
+        assert_eq!(meta.usage.as_deref(), Some("deploy"));
+    }
+
+    #[test]
+    fn repeated_keys_accumulate() {
+        let meta = parse(
+            "#!/bin/bash\n# tags: a\n# tags: b c\n# args: FILE - input\n# args: OUT - output\n\
+             # env: A=1\n#   the a\n# requires: jq\n# requires: curl\n",
+        );
+        assert_eq!(meta.tags, ["a", "b", "c"]);
+        assert_eq!(meta.args, ["FILE - input", "OUT - output"]);
+        assert_eq!(meta.env, ["A=1 the a"]);
+        assert_eq!(meta.requires, ["jq", "curl"]);
+    }
+
+    #[test]
+    fn parses_docstring_headers() {
+        let double =
+            parse("#!/usr/bin/env python3\n\"\"\"\ndescription: Resize images\ntags: img\n\"\"\"\nimport sys\n");
+        assert_eq!(double.description.as_deref(), Some("Resize images"));
+        assert_eq!(double.tags, ["img"]);
+
+        let single = parse("#!/usr/bin/env python3\n'''description: One liner'''\n");
+        assert_eq!(single.description.as_deref(), Some("One liner"));
+    }
+
+    #[test]
+    fn parses_slash_headers() {
+        let meta = parse("#!/usr/bin/env node\n// description: Print env\n// cwd: ~/src\nconsole.log(1)\n");
+        assert_eq!(meta.interpreter(), Some("node"));
+        assert_eq!(meta.description.as_deref(), Some("Print env"));
+        assert_eq!(meta.cwd.as_deref(), Some("~/src"));
+    }
+
+    #[test]
+    fn ignores_fields_in_the_body() {
+        let meta = parse("#!/bin/bash\n# description: Real\necho start\n# tags: not-a-tag\n");
+        assert_eq!(meta.description.as_deref(), Some("Real"));
+        assert!(meta.tags.is_empty());
+    }
+
+    #[test]
+    fn placeholder_description_is_not_a_description() {
+        let meta = parse("#!/bin/bash\n# description: (no description)\n");
+        assert_eq!(meta.description, None);
+        assert_eq!(meta.summary(), "(no description)");
+    }
+
+    #[test]
+    fn interpreter_looks_through_env() {
+        let meta = parse("#!/usr/bin/env -S python3 -u\n");
+        assert_eq!(meta.interpreter(), Some("python3"));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration(" 7d "), Some(Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_secs(14 * 86400)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("1h30"), None);
+    }
+
+    #[test]
+    fn set_header_field_replaces_a_field() {
+        let content = "#!/bin/bash\n# description: Old\n# tags: a\necho hi\n";
+        assert_eq!(
+            set_header_field(content, "description", Some("New")),
+            "#!/bin/bash\n# description: New\n# tags: a\necho hi\n"
+        );
+    }
+
+    #[test]
+    fn set_header_field_drops_repeats_and_continuations() {
+        let content = "#!/bin/bash\n# description: Old\n#   more old\n# tags: a\n# description: again\necho\n";
+        assert_eq!(
+            set_header_field(content, "description", Some("New")),
+            "#!/bin/bash\n# description: New\n# tags: a\necho\n"
+        );
+    }
+
+    #[test]
+    fn set_header_field_adds_after_the_last_field() {
+        let content = "#!/bin/bash\n# description: Backup\n\necho hi\n";
+        assert_eq!(
+            set_header_field(content, "tags", Some("nas")),
+            "#!/bin/bash\n# description: Backup\n# tags: nas\n\necho hi\n"
+        );
+    }
+
+    #[test]
+    fn set_header_field_adds_after_the_shebang_without_a_header() {
+        assert_eq!(
+            set_header_field("#!/bin/bash\necho hi\n", "description", Some("Greet")),
+            "#!/bin/bash\n# description: Greet\necho hi\n"
+        );
+        assert_eq!(
+            set_header_field("#!/usr/bin/env node\nconsole.log(1)\n", "tags", Some("js")),
+            "#!/usr/bin/env node\n// tags: js\nconsole.log(1)\n"
+        );
+        assert_eq!(set_header_field("echo hi\n", "tags", Some("x")), "# tags: x\necho hi\n");
+    }
+
+    #[test]
+    fn set_header_field_removes_a_field_with_its_continuations() {
+        let content = "#!/bin/bash\n# description: Deploy\n#   in two lines\n# tags: a, b\necho\n";
+        assert_eq!(set_header_field(content, "description", None), "#!/bin/bash\n# tags: a, b\necho\n");
+        assert_eq!(set_header_field("#!/bin/bash\necho\n", "tags", None), "#!/bin/bash\necho\n");
+    }
+
+    #[test]
+    fn set_header_field_keeps_slash_prefixes() {
+        let content = "#!/usr/bin/env node\n// description: Old\nconsole.log(1)\n";
+        assert_eq!(
+            set_header_field(content, "desc", Some("New")),
+            "#!/usr/bin/env node\n// description: New\nconsole.log(1)\n"
+        );
+    }
+
+    #[test]
+    fn set_header_field_edits_docstring_headers() {
+        let content = "#!/usr/bin/env python3\n\"\"\"\ndescription: Resize\ntags: img\n\"\"\"\nprint(1)\n";
+        let updated = set_header_field(content, "tags", Some("img, cli"));
+        assert_eq!(updated, content.replace("tags: img", "tags: img, cli"));
+        assert_eq!(parse(&updated).tags, ["img", "cli"]);
+    }
+
+    #[test]
+    fn set_header_field_leaves_the_body_alone() {
+        let content = "#!/bin/bash\n# description: Tagging\necho start\n# tags: body comment\n";
+        assert_eq!(
+            set_header_field(content, "tags", Some("ops")),
+            "#!/bin/bash\n# description: Tagging\n# tags: ops\necho start\n# tags: body comment\n"
+        );
+        assert_eq!(set_header_field(content, "tags", None), content);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, IsTerminal, Stdout, Write},
+    path::PathBuf,
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::{fuzzy, meta::ScriptMeta, store};
+
+const KEY_HELP: &str = "enter run · ctrl-e edit · ctrl-d delete · esc quit";
+
+/// What the user chose to do with the selected script.
+pub enum Action {
+    Run(String),
+    Edit(String),
+    Delete(String),
+}
+
+/// The picker needs a terminal on both ends; otherwise `fastbash` shows help.
+pub fn is_available() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+struct Entry {
+    name: String,
+    summary: String,
+    description: String,
+    tags: String,
+    path: PathBuf,
+}
+
+/// Restores the terminal even if drawing fails part way.
+struct RawTerminal(Stdout);
+
+impl RawTerminal {
+    fn enter() -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        queue!(stdout, EnterAlternateScreen)?;
+        stdout.flush()?;
+        Ok(RawTerminal(stdout))
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = queue!(self.0, Show, LeaveAlternateScreen);
+        let _ = self.0.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Picker {
+    entries: Vec<Entry>,
+    query: String,
+    /// Indices into `entries` matching the query, best first.
+    matches: Vec<usize>,
+    selected: usize,
+    offset: usize,
+    previews: HashMap<usize, Vec<String>>,
+}
+
+/// Runs the picker over `scripts` (shown in the given order when the query is
+/// empty). Returns `None` if the user quits without choosing.
+pub fn pick(scripts: Vec<store::Script>) -> io::Result<Option<Action>> {
+    let entries = scripts
+        .into_iter()
+        .map(|script| {
+            let meta = ScriptMeta::read(&script.path);
+            Entry {
+                summary: meta.summary().to_string(),
+                description: meta.description.unwrap_or_default(),
+                tags: meta.tags.join(" "),
+                name: script.name,
+                path: script.path,
+            }
+        })
+        .collect();
+    let mut picker = Picker {
+        entries,
+        query: String::new(),
+        matches: Vec::new(),
+        selected: 0,
+        offset: 0,
+        previews: HashMap::new(),
+    };
+    picker.filter();
+
+    let mut term = RawTerminal::enter()?;
+    loop {
+        picker.draw(&mut term.0)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(result) = picker.handle_key(key) {
+            return Ok(result);
+        }
+    }
+}
+
+impl Picker {
+    fn filter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                if self.query.trim().is_empty() {
+                    return Some((0, i));
+                }
+                let mut total = 0;
+                for word in self.query.split_whitespace() {
+                    let by_name = fuzzy::score(word, &entry.name).map(|s| s * 2);
+                    let by_description = fuzzy::score(word, &entry.description);
+                    let by_tag = fuzzy::score(word, &entry.tags);
+                    total += by_name.max(by_description).max(by_tag)?;
+                }
+                Some((total, i))
+            })
+            .collect();
+        // Stable sort keeps the incoming order among equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+        self.offset = 0;
+    }
+
+    fn current(&self) -> Option<&Entry> {
+        self.matches.get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    /// `Some` ends the picker: with an action, or `None` to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Option<Action>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let name = || self.current().map(|entry| entry.name.clone());
+        match key.code {
+            KeyCode::Esc => return Some(None),
+            KeyCode::Char('c') if ctrl => return Some(None),
+            KeyCode::Enter => return name().map(|name| Some(Action::Run(name))),
+            KeyCode::Char('e') if ctrl => return name().map(|name| Some(Action::Edit(name))),
+            KeyCode::Char('d') if ctrl => return name().map(|name| Some(Action::Delete(name))),
+            KeyCode::Delete => return name().map(|name| Some(Action::Delete(name))),
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('p' | 'k') if ctrl => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('n' | 'j') if ctrl => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.filter();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    fn preview(&mut self) -> &[String] {
+        let Some(&i) = self.matches.get(self.selected) else {
+            return &[];
+        };
+        let path = &self.entries[i].path;
+        self.previews.entry(i).or_insert_with(|| {
+            fs::read_to_string(path)
+                .map(|content| content.lines().map(|line| line.replace('\t', "    ")).collect())
+                .unwrap_or_else(|err| vec![format!("(cannot read script: {})", err)])
+        })
+    }
+
+    fn draw(&mut self, out: &mut Stdout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let list_width = (width / 2).max(30).min(width);
+        let preview_width = width.saturating_sub(list_width + 3);
+        let rows = height.saturating_sub(2);
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if rows > 0 && self.selected >= self.offset + rows {
+            self.offset = self.selected + 1 - rows;
+        }
+
+        queue!(out, Hide, Clear(ClearType::All), MoveTo(0, 0))?;
+        let status = format!("{}/{}", self.matches.len(), self.entries.len());
+        let prompt = fit(&format!("> {}", self.query), list_width.saturating_sub(status.len() + 1));
+        queue!(out, Print(&prompt), Print(" "), SetAttribute(Attribute::Dim), Print(&status))?;
+        queue!(out, SetAttribute(Attribute::Reset))?;
+
+        for row in 0..rows {
+            let Some(&i) = self.matches.get(self.offset + row) else {
+                break;
+            };
+            let entry = &self.entries[i];
+            let line = fit(&format!("  {:<20} {}", entry.name, entry.summary), list_width);
+            queue!(out, MoveTo(0, (row + 1) as u16))?;
+            if self.offset + row == self.selected {
+                queue!(out, SetAttribute(Attribute::Reverse), Print(&line), SetAttribute(Attribute::Reset))?;
+            } else {
+                queue!(out, Print(&line))?;
+            }
+        }
+
+        if preview_width > 0 {
+            let x = (list_width + 1) as u16;
+            let lines: Vec<String> = self.preview().iter().take(height.saturating_sub(1)).cloned().collect();
+            for row in 0..height.saturating_sub(1) {
+                queue!(out, MoveTo(x, row as u16), SetAttribute(Attribute::Dim), Print("│ "))?;
+                queue!(out, SetAttribute(Attribute::Reset))?;
+                if let Some(line) = lines.get(row) {
+                    queue!(out, Print(fit(line, preview_width)))?;
+                }
+            }
+        }
+
+        queue!(out, MoveTo(0, height.saturating_sub(1) as u16), SetAttribute(Attribute::Dim))?;
+        queue!(out, Print(fit(KEY_HELP, width)), SetAttribute(Attribute::Reset))?;
+        let cursor = (self.query.chars().count() + 2).min(list_width) as u16;
+        queue!(out, MoveTo(cursor, 0), Show)?;
+        out.flush()
+    }
+}
+
+/// Truncates `text` to `width` characters, marking the cut with `…`.
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.chars().take(width.saturating_sub(1)).collect();
+    if width > 0 {
+        fitted.push('…');
+    }
+    fitted
+}
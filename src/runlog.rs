@@ -0,0 +1,232 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::{json, meta, store};
+
+const LOG_FILE: &str = "runs.jsonl";
+/// The log is rotated once it grows past this size...
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// ...keeping this many older files (`runs.jsonl.1` is the newest).
+const ROTATED_FILES: usize = 3;
+const DEFAULT_LIMIT: usize = 20;
+
+/// One run of a script, as appended to the run log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub started_at: DateTime<Local>,
+    pub script: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    /// Exit code, or `None` when the script was killed by a signal or never started.
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Why the script could not be started, if it wasn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl RunRecord {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    /// `ok`, `exit 2`, `signal 9`, `timeout` or `error`.
+    pub fn status(&self) -> String {
+        if self.timed_out {
+            return "timeout".to_string();
+        }
+        match (self.exit_code, self.signal) {
+            (Some(0), _) => "ok".to_string(),
+            (Some(code), _) => format!("exit {}", code),
+            (None, Some(signal)) => format!("signal {}", signal),
+            (None, None) => "error".to_string(),
+        }
+    }
+}
+
+fn log_path() -> PathBuf {
+    store::data_dir().join(LOG_FILE)
+}
+
+fn rotated_path(n: usize) -> PathBuf {
+    store::data_dir().join(format!("{}.{}", LOG_FILE, n))
+}
+
+/// Shifts `runs.jsonl` to `runs.jsonl.1` (and so on) once it is too big.
+fn rotate_if_needed() -> io::Result<()> {
+    let size = fs::metadata(log_path()).map(|meta| meta.len()).unwrap_or(0);
+    if size < MAX_LOG_BYTES {
+        return Ok(());
+    }
+    for n in (1..ROTATED_FILES).rev() {
+        let from = rotated_path(n);
+        if from.exists() {
+            fs::rename(from, rotated_path(n + 1))?;
+        }
+    }
+    fs::rename(log_path(), rotated_path(1))
+}
+
+fn append(record: &RunRecord) -> io::Result<()> {
+    fs::create_dir_all(store::data_dir())?;
+    rotate_if_needed()?;
+    let mut file = OpenOptions::new().create(true).append(true).open(log_path())?;
+    let line = serde_json::to_string(record).expect("Failed to serialize run record");
+    writeln!(file, "{}", line)
+}
+
+/// Appends a run to the log; a failure only warns, since the run itself is done.
+pub fn record_or_warn(record: &RunRecord) {
+    if let Err(err) = append(record) {
+        eprintln!("Warning: could not write the run log: {}", err);
+    }
+}
+
+/// Log files from oldest to newest.
+fn log_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = (1..=ROTATED_FILES).rev().map(rotated_path).collect();
+    files.push(log_path());
+    files
+}
+
+/// Every logged run, oldest first. Unreadable lines are skipped.
+pub fn records() -> Vec<RunRecord> {
+    log_files()
+        .into_iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<RunRecord>>()
+        })
+        .collect()
+}
+
+/// Points logged runs of `from` at `to` after a rename.
+pub fn rename(from: &str, to: &str) -> io::Result<()> {
+    for path in log_files() {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut changed = false;
+        let mut out = String::with_capacity(contents.len());
+        for line in contents.lines() {
+            match serde_json::from_str::<RunRecord>(line) {
+                Ok(mut record) if record.script == from => {
+                    record.script = to.to_string();
+                    out.push_str(&serde_json::to_string(&record).expect("Failed to serialize run record"));
+                    changed = true;
+                }
+                _ => out.push_str(line),
+            }
+            out.push('\n');
+        }
+        if changed {
+            fs::write(&path, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// A point in time given as an age (`2h`, `7d`), a date (`2024-05-01`) or a
+/// date and time (`2024-05-01 14:30`).
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    if let Some(age) = meta::parse_duration(value) {
+        return Some(Local::now() - chrono::Duration::from_std(age).ok()?);
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+fn parse_time_or_exit(flag: &str, value: &str) -> DateTime<Local> {
+    parse_time(value).unwrap_or_else(|| {
+        let hint = "use an age like 2h or 7d, or a date like 2024-05-01";
+        json::fail("invalid-argument", format!("Invalid time for {} '{}'; {}", flag, value, hint))
+    })
+}
+
+/// `log [script] [--failed] [--since <when>] [--until <when>] [-n <count>] [--all] [--json]`
+pub fn log_command(args: &[String]) {
+    let usage = "Usage: fastbash log [<script>] [--failed] [--since <when>] [--until <when>] [-n <count>] \
+                 [--all] [--json]";
+    let mut script = None;
+    let mut failed = false;
+    let mut since = None;
+    let mut until = None;
+    let mut limit = Some(DEFAULT_LIMIT);
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().unwrap_or_else(|| json::fail("usage", usage));
+        match arg.as_str() {
+            "--failed" | "-f" => failed = true,
+            "--json" => json::enable(),
+            "--since" => since = Some(parse_time_or_exit(arg, &value())),
+            "--until" => until = Some(parse_time_or_exit(arg, &value())),
+            "--all" | "-a" => limit = None,
+            "-n" => {
+                let count = value();
+                let invalid = |_| json::fail("invalid-argument", format!("Invalid count '{}'", count));
+                limit = Some(count.parse().unwrap_or_else(invalid));
+            }
+            _ if script.is_none() && !arg.starts_with('-') => script = Some(arg.clone()),
+            _ => json::fail("usage", usage),
+        }
+    }
+
+    let mut runs: Vec<RunRecord> = records()
+        .into_iter()
+        .filter(|run| {
+            script.as_ref().is_none_or(|name| run.script == *name || run.script.starts_with(&format!("{}/", name)))
+        })
+        .filter(|run| !failed || !run.succeeded())
+        .filter(|run| since.is_none_or(|since| run.started_at >= since))
+        .filter(|run| until.is_none_or(|until| run.started_at <= until))
+        .collect();
+    if let Some(limit) = limit {
+        let skip = runs.len().saturating_sub(limit);
+        runs.drain(..skip);
+    }
+    if json::enabled() {
+        let objects: Vec<_> = runs.iter().map(json::run_object).collect();
+        json::print(&objects.into());
+        return;
+    }
+    if runs.is_empty() {
+        let filtered = script.is_some() || failed || since.is_some() || until.is_some();
+        println!("{}", if filtered { "No matching runs" } else { "No runs logged yet" });
+        return;
+    }
+
+    println!("{:<19}  {:<20} {:<9} {:>8}  ARGS", "STARTED", "SCRIPT", "STATUS", "TIME");
+    for run in &runs {
+        println!(
+            "{:<19}  {:<20} {:<9} {:>8}  {}",
+            run.started_at.format("%Y-%m-%d %H:%M:%S"),
+            run.script,
+            run.status(),
+            meta::format_duration(run.duration()),
+            run.args.join(" ")
+        );
+    }
+}
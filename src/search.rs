@@ -0,0 +1,160 @@
+use std::{fs, process::exit};
+
+use regex::RegexBuilder;
+use serde_json::json;
+
+use crate::{fuzzy, json, meta::ScriptMeta, store, term};
+
+/// Options shared by `search` and `grep`.
+struct Filters {
+    pattern: String,
+    tags: Vec<String>,
+    ignore_case: bool,
+}
+
+fn parse_filters(args: &[String], usage: &str) -> Filters {
+    let mut pattern = None;
+    let mut tags = Vec::new();
+    let mut ignore_case = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--tag" | "-t" => match iter.next() {
+                Some(tag) => tags.push(tag.to_lowercase()),
+                None => json::fail("usage", usage),
+            },
+            "--json" => json::enable(),
+            "-i" | "--ignore-case" => ignore_case = true,
+            _ if pattern.is_none() => pattern = Some(arg.clone()),
+            _ => json::fail("usage", usage),
+        }
+    }
+    let Some(pattern) = pattern else {
+        json::fail("usage", usage);
+    };
+    Filters { pattern, tags, ignore_case }
+}
+
+/// Every visible script with its metadata, restricted to those carrying all `tags`.
+pub fn tagged_scripts(tags: &[String]) -> Vec<(store::Script, ScriptMeta)> {
+    store::all_scripts()
+        .into_iter()
+        .map(|script| {
+            let meta = ScriptMeta::read(&script.path);
+            (script, meta)
+        })
+        .filter(|(_, meta)| meta.has_tags(tags))
+        .collect()
+}
+
+/// Ranks scripts by fuzzy matching each query word against the name,
+/// description and tags.
+pub fn search_command(args: &[String]) {
+    let filters = parse_filters(args, "Usage: fastbash search <query> [--tag <tag>]... [--json]");
+    let words: Vec<&str> = filters.pattern.split_whitespace().collect();
+
+    let mut results: Vec<(i64, store::Script, ScriptMeta)> = tagged_scripts(&filters.tags)
+        .into_iter()
+        .filter_map(|(script, meta)| {
+            let description = meta.description.as_deref().unwrap_or("");
+            let tags = meta.tags.join(" ");
+            let mut total = 0;
+            for word in &words {
+                // Name matches count double
+                let by_name = fuzzy::score(word, &script.name).map(|s| s * 2);
+                let by_description = fuzzy::score(word, description);
+                let by_tag = fuzzy::score(word, &tags);
+                total += by_name.max(by_description).max(by_tag)?;
+            }
+            Some((total, script, meta))
+        })
+        .collect();
+    results.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    if json::enabled() {
+        let objects: Vec<_> = results
+            .iter()
+            .map(|(score, script, meta)| {
+                let mut object = json::script_object(script, meta);
+                object["score"] = json!(score);
+                object
+            })
+            .collect();
+        json::print(&json!(objects));
+    } else if results.is_empty() {
+        eprintln!("No scripts match '{}'", filters.pattern);
+    } else {
+        for (_, script, meta) in &results {
+            println!("{:<20} {}", script.name, meta.summary());
+        }
+    }
+    if results.is_empty() {
+        exit(1);
+    }
+}
+
+/// Prints `name:line:text` for every line of every script matching a regex.
+pub fn grep_command(args: &[String]) {
+    let filters = parse_filters(args, "Usage: fastbash grep <regex> [-i] [--tag <tag>]... [--json]");
+    let re = match RegexBuilder::new(&filters.pattern).case_insensitive(filters.ignore_case).build() {
+        Ok(re) => re,
+        Err(err) => json::fail("invalid-argument", format!("Invalid regex '{}': {}", filters.pattern, err)),
+    };
+    let color = !json::enabled() && term::color_enabled();
+
+    let mut found = 0;
+    let mut records = Vec::new();
+    for (script, _) in tagged_scripts(&filters.tags) {
+        let Ok(content) = fs::read_to_string(&script.path) else {
+            continue;
+        };
+        for (i, line) in content.lines().enumerate() {
+            if !re.is_match(line) {
+                continue;
+            }
+            found += 1;
+            if json::enabled() {
+                records.push(json!({
+                    "name": script.name,
+                    "path": script.path,
+                    "line": i + 1,
+                    "text": line,
+                }));
+            } else if color {
+                let line = re.replace_all(line, "\x1b[1;31m$0\x1b[0m");
+                println!("\x1b[35m{}\x1b[0m:\x1b[32m{}\x1b[0m:{}", script.name, i + 1, line);
+            } else {
+                println!("{}:{}:{}", script.name, i + 1, line);
+            }
+        }
+    }
+
+    if json::enabled() {
+        json::print(&json!(records));
+    }
+    if found == 0 {
+        exit(1);
+    }
+}
+
+/// Lines of other scripts that invoke `name` through fastbash, as `(script, line)`.
+pub fn callers_of(name: &str) -> Vec<(String, usize)> {
+    let pattern = format!(r#"\bfastbash\s+(run\s+)?{}(\s|$|[;|&)"'])"#, regex::escape(name));
+    let re = regex::Regex::new(&pattern).expect("Failed to build caller regex");
+    let mut callers = Vec::new();
+    for script in store::all_scripts() {
+        if script.name == name {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&script.path) else {
+            continue;
+        };
+        for (i, line) in content.lines().enumerate() {
+            if re.is_match(line) {
+                callers.push((script.name.clone(), i + 1));
+            }
+        }
+    }
+    callers
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Local};
+use serde_json::json;
+
+use crate::{
+    json, meta,
+    runlog::{self, RunRecord},
+    store,
+};
+
+const DEFAULT_LIMIT: usize = 10;
+
+/// Points a run earns by age, newest first: frequently *and* recently used
+/// scripts rank highest, and old habits fade out.
+const FRECENCY_BUCKETS: &[(i64, f64)] = &[
+    (4, 100.0),       // last 4 hours
+    (24, 80.0),       // last day
+    (24 * 7, 60.0),   // last week
+    (24 * 30, 40.0),  // last month
+    (24 * 90, 20.0),  // last three months
+    (i64::MAX, 10.0), // older
+];
+
+/// Aggregated runs of one script.
+pub struct Usage {
+    pub runs: usize,
+    pub failures: usize,
+    pub total: Duration,
+    pub last_run: DateTime<Local>,
+    pub frecency: f64,
+}
+
+impl Usage {
+    pub fn average(&self) -> Duration {
+        self.total / self.runs.max(1) as u32
+    }
+
+    pub fn failure_rate(&self) -> f64 {
+        self.failures as f64 / self.runs.max(1) as f64
+    }
+}
+
+fn frecency_points(run: &RunRecord, now: DateTime<Local>) -> f64 {
+    let hours = (now - run.started_at).num_hours();
+    FRECENCY_BUCKETS
+        .iter()
+        .find(|(limit, _)| hours < *limit)
+        .map_or(0.0, |(_, points)| *points)
+}
+
+/// Usage of every script that has logged runs, keyed by script name.
+pub fn usage() -> HashMap<String, Usage> {
+    let now = Local::now();
+    let mut usage: HashMap<String, Usage> = HashMap::new();
+    for run in runlog::records() {
+        let entry = usage.entry(run.script.clone()).or_insert(Usage {
+            runs: 0,
+            failures: 0,
+            total: Duration::ZERO,
+            last_run: run.started_at,
+            frecency: 0.0,
+        });
+        entry.runs += 1;
+        if !run.succeeded() {
+            entry.failures += 1;
+        }
+        entry.total += run.duration();
+        entry.last_run = entry.last_run.max(run.started_at);
+        entry.frecency += frecency_points(&run, now);
+    }
+    usage
+}
+
+/// Orders scripts most frecent first. Scripts that were never run keep their
+/// relative (name) order at the end.
+pub fn sort_by_frecency(scripts: &mut [store::Script]) {
+    let usage = usage();
+    let score = |script: &store::Script| usage.get(&script.name).map_or(0.0, |usage| usage.frecency);
+    scripts.sort_by(|a, b| score(b).total_cmp(&score(a)));
+}
+
+/// `stats [-n <count>] [--all] [--json]`: most-used scripts with failure
+/// rates and average durations.
+pub fn stats_command(args: &[String]) {
+    let usage_text = "Usage: fastbash stats [-n <count>] [--all] [--json]";
+    let mut limit = Some(DEFAULT_LIMIT);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--all" | "-a" => limit = None,
+            "--json" => json::enable(),
+            "-n" => match iter.next().and_then(|count| count.parse().ok()) {
+                Some(count) => limit = Some(count),
+                None => json::fail("usage", usage_text),
+            },
+            _ => json::fail("usage", usage_text),
+        }
+    }
+
+    let mut usage: Vec<(String, Usage)> = usage().into_iter().collect();
+    usage.sort_by(|(a_name, a), (b_name, b)| b.runs.cmp(&a.runs).then_with(|| a_name.cmp(b_name)));
+    if json::enabled() {
+        let objects: Vec<_> = usage
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(name, usage)| {
+                json!({
+                    "script": name,
+                    "runs": usage.runs,
+                    "failures": usage.failures,
+                    "failure_rate": usage.failure_rate(),
+                    "average_ms": usage.average().as_millis() as u64,
+                    "last_run": usage.last_run,
+                    "frecency": usage.frecency,
+                })
+            })
+            .collect();
+        json::print(&objects.into());
+        return;
+    }
+    if usage.is_empty() {
+        println!("No runs logged yet");
+        return;
+    }
+    let total_runs: usize = usage.iter().map(|(_, usage)| usage.runs).sum();
+    let total_failures: usize = usage.iter().map(|(_, usage)| usage.failures).sum();
+
+    println!(
+        "{} run(s) of {} script(s), {} failed ({:.0}%)\n",
+        total_runs,
+        usage.len(),
+        total_failures,
+        100.0 * total_failures as f64 / total_runs as f64
+    );
+    println!("{:<20} {:>6} {:>7} {:>6} {:>9}  LAST RUN", "SCRIPT", "RUNS", "FAILED", "FAIL%", "AVG TIME");
+    for (name, usage) in usage.iter().take(limit.unwrap_or(usize::MAX)) {
+        println!(
+            "{:<20} {:>6} {:>7} {:>5.0}% {:>9}  {}",
+            name,
+            usage.runs,
+            usage.failures,
+            100.0 * usage.failure_rate(),
+            meta::format_duration(usage.average()),
+            usage.last_run.format("%Y-%m-%d %H:%M")
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::{
+    config::{self, expand_home},
+    json,
+};
+
+static SCRIPTS_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set from the global `--scripts-dir` flag; takes precedence over everything else.
+pub fn set_scripts_dir_override(dir: PathBuf) {
+    SCRIPTS_DIR_OVERRIDE.set(absolute(dir)).expect("Scripts directory override set twice");
+}
+
+/// Makes a configured directory absolute against the current directory, so
+/// script paths stay valid when a script runs somewhere else (`# cwd:`).
+fn absolute(path: PathBuf) -> PathBuf {
+    std::path::absolute(&path).unwrap_or(path)
+}
+
+/// Root of the fastbash data directory.
+///
+/// Resolved from `FASTBASH_HOME`, then `home` in the config file, then
+/// `$XDG_DATA_HOME/fastbash`. A store left at the legacy `~/.fastbash`
+/// location is moved to the XDG location the first time it is needed, unless
+/// scripts are kept elsewhere anyway (`--scripts-dir` or `scripts_dir`).
+pub fn fastbash_home() -> PathBuf {
+    static HOME: OnceLock<PathBuf> = OnceLock::new();
+    HOME.get_or_init(|| {
+        if let Some(home) = env::var_os("FASTBASH_HOME").filter(|v| !v.is_empty()) {
+            return absolute(expand_home(&home.to_string_lossy()));
+        }
+        if let Some(home) = &config::get().home {
+            return absolute(expand_home(home));
+        }
+        let default = dirs::data_dir()
+            .expect("Could not determine data directory")
+            .join("fastbash");
+        if SCRIPTS_DIR_OVERRIDE.get().is_some() || config::get().scripts_dir.is_some() {
+            return default;
+        }
+        migrate_legacy_home(default)
+    })
+    .clone()
+}
+
+/// Where templates and the run log live. Under `--scripts-dir` this is the
+/// hidden `.data/` inside that store, so a throwaway store (e.g. in CI)
+/// leaves the user's own data alone.
+pub fn data_dir() -> PathBuf {
+    match SCRIPTS_DIR_OVERRIDE.get() {
+        Some(dir) => dir.join(".data"),
+        None => fastbash_home(),
+    }
+}
+
+fn migrate_legacy_home(target: PathBuf) -> PathBuf {
+    let Some(legacy) = dirs::home_dir().map(|home| home.join(".fastbash")) else {
+        return target;
+    };
+    if !legacy.is_dir() || target.exists() {
+        return target;
+    }
+
+    if let Some(parent) = target.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    match fs::rename(&legacy, &target) {
+        Ok(()) => {
+            eprintln!("Moved script store from {} to {}", legacy.display(), target.display());
+            target
+        }
+        Err(err) => {
+            eprintln!(
+                "Could not move {} to {}: {}\nUsing the old location; move it by hand to silence this warning.",
+                legacy.display(),
+                target.display(),
+                err
+            );
+            legacy
+        }
+    }
+}
+
+/// Directory scripts are stored in. It is not created here; see [`ensure_scripts_dir`].
+pub fn get_scripts_dir() -> PathBuf {
+    if let Some(dir) = SCRIPTS_DIR_OVERRIDE.get() {
+        return dir.clone();
+    }
+    if env::var_os("FASTBASH_HOME").is_none_or(|v| v.is_empty())
+        && let Some(dir) = &config::get().scripts_dir
+    {
+        return absolute(expand_home(dir));
+    }
+    fastbash_home().join("scripts")
+}
+
+/// Like [`get_scripts_dir`], but creates the directory for commands that write to it.
+pub fn ensure_scripts_dir() -> PathBuf {
+    let dir = get_scripts_dir();
+    if let Err(err) = fs::create_dir_all(&dir) {
+        json::fail("io", format!("Failed to create script directory {}: {}", dir.display(), err));
+    }
+    dir
+}
+
+const DEFAULT_SYSTEM_DIR: &str = "/usr/share/fastbash/scripts";
+
+/// One of the stores consulted when resolving a script, in lookup order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LayerKind {
+    /// `.fastbash/` in the current directory or the nearest parent.
+    Project,
+    /// The user's own store, see [`get_scripts_dir`].
+    User,
+    /// Shared, read-only scripts, e.g. installed by a package.
+    System,
+}
+
+impl LayerKind {
+    pub fn is_read_only(self) -> bool {
+        self == LayerKind::System
+    }
+}
+
+impl fmt::Display for LayerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LayerKind::Project => "project",
+            LayerKind::User => "user",
+            LayerKind::System => "system",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub kind: LayerKind,
+    pub dir: PathBuf,
+}
+
+/// A script found in one of the layers.
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub name: String,
+    pub path: PathBuf,
+    /// Directory of the layer the script lives in.
+    pub root: PathBuf,
+    pub layer: LayerKind,
+    /// Lower layers that have a script with the same name.
+    pub shadows: Vec<LayerKind>,
+}
+
+/// The search path: project store (if any), user store, system store.
+///
+/// A `.fastbash/` that is really the data directory (or holds the user store)
+/// is not a project, or its logs and history would show up as scripts.
+pub fn layers() -> Vec<Layer> {
+    let user = get_scripts_dir();
+    let mut layers = Vec::new();
+    if let Some(dir) = find_project_dir()
+        && !user.starts_with(&dir)
+        && dir != fastbash_home()
+    {
+        layers.push(Layer { kind: LayerKind::Project, dir });
+    }
+    layers.push(Layer { kind: LayerKind::User, dir: user });
+    let system = config::get().system_dir.as_deref().unwrap_or(DEFAULT_SYSTEM_DIR);
+    layers.push(Layer { kind: LayerKind::System, dir: expand_home(system) });
+    layers
+}
+
+/// Walks up from the current directory looking for a `.fastbash/` directory.
+/// The home directory is skipped so the legacy `~/.fastbash` store is never
+/// mistaken for a project.
+fn find_project_dir() -> Option<PathBuf> {
+    let home = dirs::home_dir();
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .filter(|dir| Some(*dir) != home.as_deref())
+        .map(|dir| dir.join(".fastbash"))
+        .find(|dir| dir.is_dir())
+}
+
+/// Finds `name` in the first layer that has it.
+pub fn resolve(name: &str) -> Option<Script> {
+    let mut found: Option<Script> = None;
+    for layer in layers() {
+        let path = layer.dir.join(name);
+        if !path.is_file() {
+            continue;
+        }
+        match &mut found {
+            Some(script) => script.shadows.push(layer.kind),
+            None => {
+                found = Some(Script {
+                    name: name.to_string(),
+                    path,
+                    root: layer.dir,
+                    layer: layer.kind,
+                    shadows: Vec::new(),
+                })
+            }
+        }
+    }
+    found
+}
+
+/// A script in the user store, whether or not it exists yet.
+pub fn user_script(name: &str) -> Script {
+    let root = get_scripts_dir();
+    Script {
+        name: name.to_string(),
+        path: root.join(name),
+        root,
+        layer: LayerKind::User,
+        shadows: Vec::new(),
+    }
+}
+
+/// Every script visible through the search path, sorted by name. Where
+/// several layers define the same name, the highest layer wins.
+pub fn all_scripts() -> Vec<Script> {
+    let mut scripts: BTreeMap<String, Script> = BTreeMap::new();
+    for layer in layers() {
+        for name in script_names(&layer.dir) {
+            match scripts.get_mut(&name) {
+                Some(script) => script.shadows.push(layer.kind),
+                None => {
+                    let script = Script {
+                        name: name.clone(),
+                        path: layer.dir.join(&name),
+                        root: layer.dir.clone(),
+                        layer: layer.kind,
+                        shadows: Vec::new(),
+                    };
+                    scripts.insert(name, script);
+                }
+            }
+        }
+    }
+    scripts.into_values().collect()
+}
+
+/// Entries starting with a dot (e.g. `.git`) are never treated as scripts or namespaces.
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(true)
+}
+
+/// Returns every script under `dir` as a `group/name` path.
+pub fn script_names(dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    collect_names(dir, "", &mut names);
+    names
+}
+
+fn collect_names(dir: &Path, prefix: &str, names: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if is_hidden(&path) {
+            continue;
+        }
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_names(&path, &format!("{}/", name), names);
+        } else if path.is_file() {
+            names.push(name);
+        }
+    }
+}
+
+/// Removes now-empty namespace directories between `path` and the store root.
+pub fn prune_empty_dirs(root: &Path, path: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Checks that `name` is a plain `group/name` path inside the store: no
+/// empty, `.`/`..` or hidden components, no absolute paths, no control
+/// characters and no leading dashes.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Script name cannot be empty".to_string());
+    }
+    if name.starts_with('/') {
+        return Err(format!("Script name '{}' must not be an absolute path", name));
+    }
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        return Err(format!("Script name {:?} contains the control character {:?}", name, c));
+    }
+    for part in name.split('/') {
+        if part.is_empty() {
+            return Err(format!("Script name '{}' contains an empty path component", name));
+        }
+        if part == "." || part == ".." {
+            return Err(format!("Script name '{}' must not contain '{}'", name, part));
+        }
+        if part.starts_with('.') {
+            return Err(format!("Script name '{}' must not contain hidden components", name));
+        }
+        if part.starts_with('-') {
+            return Err(format!("Script name '{}' must not start with '-'", name));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_and_grouped_names() {
+        assert_eq!(validate_name("deploy"), Ok(()));
+        assert_eq!(validate_name("db/backup-nightly"), Ok(()));
+        assert_eq!(validate_name("a/b/c_1.sh"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_empty_names_and_components() {
+        assert!(validate_name("").is_err());
+        assert!(validate_name("db//backup").is_err());
+        assert!(validate_name("db/").is_err());
+    }
+
+    #[test]
+    fn rejects_parent_and_current_directory() {
+        assert!(validate_name("..").is_err());
+        assert!(validate_name("../outside").is_err());
+        assert!(validate_name("db/../../etc/passwd").is_err());
+        assert!(validate_name("./deploy").is_err());
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        assert!(validate_name("/etc/passwd").is_err());
+        assert!(validate_name("/").is_err());
+    }
+
+    #[test]
+    fn rejects_control_characters() {
+        assert!(validate_name("dep\nloy").is_err());
+        assert!(validate_name("deploy\0").is_err());
+        assert!(validate_name("db/\x1b[31mred").is_err());
+    }
+
+    #[test]
+    fn rejects_leading_dashes_in_any_component() {
+        assert!(validate_name("-rf").is_err());
+        assert!(validate_name("db/--help").is_err());
+        assert_eq!(validate_name("db/back-up"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_hidden_components() {
+        assert!(validate_name(".git").is_err());
+        assert!(validate_name("db/.hidden").is_err());
+        assert!(validate_name(".history/index/x.toml").is_err());
+    }
+}
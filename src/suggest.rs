@@ -0,0 +1,133 @@
+use std::process::exit;
+
+use crate::{json, store};
+
+const MAX_SUGGESTIONS: usize = 5;
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Names close to `name`: prefix matches first, then by edit distance to the
+/// full name or to the name without its group.
+pub fn suggestions(name: &str, names: &[String]) -> Vec<String> {
+    let threshold = (name.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &String)> = names
+        .iter()
+        .filter_map(|candidate| {
+            if candidate.starts_with(name) {
+                return Some((0, candidate));
+            }
+            let leaf = candidate.rsplit('/').next().unwrap_or(candidate);
+            let distance = levenshtein(name, candidate).min(levenshtein(name, leaf) + 1);
+            (distance <= threshold).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name.clone()).collect()
+}
+
+/// The only script whose name starts with `prefix`, if there is exactly one.
+pub fn unique_prefix_match(prefix: &str, names: &[String]) -> Option<String> {
+    let mut matches = names.iter().filter(|name| name.starts_with(prefix));
+    let first = matches.next()?;
+    matches.next().is_none().then(|| first.clone())
+}
+
+pub fn visible_names() -> Vec<String> {
+    store::all_scripts().into_iter().map(|script| script.name).collect()
+}
+
+/// Reports a missing script, with the closest names when suggestions are enabled.
+pub fn not_found(name: &str, suggest: bool) -> ! {
+    let candidates = if suggest { suggestions(name, &visible_names()) } else { Vec::new() };
+    if json::enabled() {
+        let mut error = json::error_object("not-found", format!("Script '{}' not found", name));
+        error["error"]["suggestions"] = candidates.into();
+        eprintln!("{}", error);
+        exit(1);
+    }
+    eprintln!("Script '{}' not found", name);
+    if suggest {
+        match candidates.as_slice() {
+            [] => {}
+            [only] => eprintln!("Did you mean '{}'?", only),
+            several => {
+                eprintln!("Did you mean one of these?");
+                for candidate in several {
+                    eprintln!("    {}", candidate);
+                }
+            }
+        }
+    }
+    exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("deploy", "deploy"), 0);
+        assert_eq!(levenshtein("deplyo", "deploy"), 2);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn prefix_matches_come_first() {
+        let names = names(&["deploy-prod", "delpoy", "deploy"]);
+        assert_eq!(suggestions("deploy", &names), ["deploy", "deploy-prod", "delpoy"]);
+    }
+
+    #[test]
+    fn typos_within_the_threshold_are_suggested() {
+        let names = names(&["backup", "build", "clean"]);
+        assert_eq!(suggestions("bakup", &names), ["backup"]);
+        assert!(suggestions("xyz", &names).is_empty());
+    }
+
+    #[test]
+    fn the_name_without_its_group_is_matched_too() {
+        let names = names(&["db/backup", "web/deploy"]);
+        assert_eq!(suggestions("backup", &names), ["db/backup"]);
+        assert_eq!(suggestions("deploi", &names), ["web/deploy"]);
+    }
+
+    #[test]
+    fn suggestions_are_capped() {
+        let names = names(&["t1", "t2", "t3", "t4", "t5", "t6", "t7"]);
+        assert_eq!(suggestions("t", &names).len(), MAX_SUGGESTIONS);
+    }
+
+    #[test]
+    fn unique_prefix_needs_exactly_one_match() {
+        let names = names(&["deploy", "db/backup", "db/restore"]);
+        assert_eq!(unique_prefix_match("dep", &names).as_deref(), Some("deploy"));
+        assert_eq!(unique_prefix_match("db/r", &names).as_deref(), Some("db/restore"));
+        assert_eq!(unique_prefix_match("d", &names), None);
+        assert_eq!(unique_prefix_match("zzz", &names), None);
+    }
+
+    #[test]
+    fn unique_prefix_accepts_an_exact_name() {
+        let names = names(&["deploy"]);
+        assert_eq!(unique_prefix_match("deploy", &names).as_deref(), Some("deploy"));
+    }
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, fs};
+
+use serde_json::json;
+
+use crate::{git, history, json, meta, resolve_or_exit, store};
+
+/// `tag add <script> <tag>...` / `tag rm <script> <tag>...` rewrite the
+/// script's `# tags:` header in place.
+pub fn tag_command(args: &[String]) {
+    let usage = "Usage: fastbash tag add|rm <script> <tag>...";
+    let (adding, name, tags) = match args {
+        [action, name, tags @ ..] if !tags.is_empty() && (action == "add" || action == "rm") => {
+            (action == "add", name, tags)
+        }
+        _ => json::fail("usage", usage),
+    };
+    let tags: Vec<String> = tags.iter().map(|tag| tag.to_lowercase()).collect();
+    let invalid = |tag: &&String| tag.is_empty() || tag.contains(|c: char| c == ',' || c.is_whitespace());
+    if let Some(bad) = tags.iter().find(invalid) {
+        let message = format!("Invalid tag '{}': tags cannot be empty or contain commas or spaces", bad);
+        json::fail("invalid-argument", message);
+    }
+
+    let script = resolve_or_exit(name);
+    if script.layer.is_read_only() {
+        let message = format!("Script '{}' is in the read-only {} store and cannot be tagged", name, script.layer);
+        json::fail("read-only", message);
+    }
+    let content = fs::read_to_string(&script.path)
+        .unwrap_or_else(|err| json::fail("io", format!("Failed to read '{}': {}", name, err)));
+    let mut current = meta::ScriptMeta::parse(content.lines()).tags;
+    let has = |tags: &[String], tag: &str| tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+
+    let action = if adding { "tag add" } else { "tag rm" };
+    let mut changed = Vec::new();
+    for tag in &tags {
+        if adding && !has(&current, tag) {
+            current.push(tag.clone());
+            changed.push(tag.as_str());
+        } else if !adding && has(&current, tag) {
+            current.retain(|t| !t.eq_ignore_ascii_case(tag));
+            changed.push(tag.as_str());
+        }
+    }
+    if changed.is_empty() {
+        let state = if adding { "already has" } else { "does not have" };
+        json::done(
+            json!({ "action": action, "name": script.name, "changed": changed, "tags": current }),
+            format_args!("Script '{}' {} tag(s) {}", name, state, tags.join(", ")),
+        );
+        return;
+    }
+
+    let joined = current.join(", ");
+    let value = (!current.is_empty()).then_some(joined.as_str());
+    if let Err(err) = fs::write(&script.path, meta::set_header_field(&content, "tags", value)) {
+        json::fail("io", format!("Failed to update '{}': {}", name, err));
+    }
+    history::record_or_warn(&script, "tag");
+    git::commit_script(&script, "tag");
+    let verb = if adding { "Tagged" } else { "Untagged" };
+    json::done(
+        json!({ "action": action, "name": script.name, "changed": changed, "tags": current }),
+        format_args!("{} '{}': {}", verb, name, changed.join(", ")),
+    );
+}
+
+/// `tags [--json]` lists every tag in use with the number of scripts carrying it.
+pub fn tags_command(args: &[String]) {
+    match args {
+        [] => {}
+        [flag] if flag == "--json" => json::enable(),
+        _ => json::fail("usage", "Usage: fastbash tags [--json]"),
+    }
+
+    let mut tagged: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for script in store::all_scripts() {
+        let mut tags: Vec<String> =
+            meta::ScriptMeta::read(&script.path).tags.iter().map(|tag| tag.to_lowercase()).collect();
+        tags.sort();
+        tags.dedup();
+        for tag in tags {
+            tagged.entry(tag).or_default().push(script.name.clone());
+        }
+    }
+    let mut tagged: Vec<(String, Vec<String>)> = tagged.into_iter().collect();
+    // Most used first; the map already ordered equal counts by name
+    tagged.sort_by_key(|(_, scripts)| std::cmp::Reverse(scripts.len()));
+
+    if json::enabled() {
+        let objects: Vec<_> = tagged
+            .iter()
+            .map(|(tag, scripts)| json!({ "tag": tag, "count": scripts.len(), "scripts": scripts }))
+            .collect();
+        json::print(&objects.into());
+        return;
+    }
+    if tagged.is_empty() {
+        println!("No tags yet (add one with `fastbash tag add <script> <tag>`)");
+        return;
+    }
+    for (tag, scripts) in &tagged {
+        println!("{:<20} {}", tag, scripts.len());
+    }
+}
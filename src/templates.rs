@@ -0,0 +1,200 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    sync::LazyLock,
+};
+
+use regex::{Captures, Regex};
+use serde_json::json;
+
+use crate::{config, json, open_in_editor, store};
+
+/// Template used by `create` when none is given. A user template with the
+/// same name replaces it.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("default", "#!/bin/bash\n# description: (no description)\n"),
+    (
+        "bash",
+        "#!/usr/bin/env bash\n\
+         # description: (no description)\n\
+         # author: {{author}}\n\
+         # created: {{date}}\n\
+         set -euo pipefail\n\
+         IFS=$'\\n\\t'\n\
+         \n",
+    ),
+    (
+        "sh",
+        "#!/bin/sh\n\
+         # description: (no description)\n\
+         # author: {{author}}\n\
+         # created: {{date}}\n\
+         set -eu\n\
+         \n",
+    ),
+    (
+        "python",
+        "#!/usr/bin/env python3\n\
+         \"\"\"\n\
+         description: (no description)\n\
+         author: {{author}}\n\
+         \"\"\"\n\
+         import sys\n\
+         \n\
+         \n\
+         def main(argv):\n    \
+             return 0\n\
+         \n\
+         \n\
+         if __name__ == \"__main__\":\n    \
+             sys.exit(main(sys.argv[1:]))\n",
+    ),
+    (
+        "node",
+        "#!/usr/bin/env node\n\
+         // description: (no description)\n\
+         // author: {{author}}\n\
+         // created: {{date}}\n\
+         'use strict';\n\
+         \n",
+    ),
+];
+
+static PLACEHOLDER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{\s*(\w+)\s*\}\}").unwrap());
+
+pub fn templates_dir() -> PathBuf {
+    store::data_dir().join("templates")
+}
+
+fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN_TEMPLATES.iter().find(|(n, _)| *n == name).map(|(_, body)| *body)
+}
+
+/// Looks up a template, preferring the user's templates directory over the built-ins.
+pub fn load(name: &str) -> Option<String> {
+    let path = templates_dir().join(name);
+    if path.is_file() {
+        return fs::read_to_string(&path).ok();
+    }
+    builtin(name).map(str::to_string)
+}
+
+/// Fills in `{{name}}`, `{{date}}` and `{{author}}`. Unknown placeholders are left as they are.
+pub fn render(template: &str, script_name: &str) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |caps: &Captures| match &caps[1] {
+            "name" => script_name.to_string(),
+            "date" => chrono::Local::now().format("%Y-%m-%d").to_string(),
+            "author" => author(),
+            _ => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// `author` from the config file, then git's `user.name`, then `$USER`.
+fn author() -> String {
+    if let Some(author) = &config::get().author {
+        return author.clone();
+    }
+    let git_name = Command::new("git")
+        .args(["config", "--get", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty());
+    git_name
+        .or_else(|| env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub fn templates_command(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None | Some("ls") => list_templates(),
+        Some("add") if args.len() >= 2 => add_template(&args[1], &args[2..]),
+        Some("edit") if args.len() == 2 => edit_template(&args[1]),
+        _ => json::fail("usage", "Usage: fastbash templates [ls | add <name> [--from <file>] | edit <name>]"),
+    }
+}
+
+fn list_templates() {
+    let mut templates: BTreeMap<String, &str> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, _)| (name.to_string(), "built-in"))
+        .collect();
+    if let Ok(entries) = fs::read_dir(templates_dir()) {
+        for entry in entries.flatten() {
+            if !entry.path().is_file() || store::is_hidden(&entry.path()) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let source = if builtin(&name).is_some() { "user, overrides built-in" } else { "user" };
+            templates.insert(name, source);
+        }
+    }
+
+    let mut objects = Vec::new();
+    for (name, source) in templates {
+        let shebang = load(&name)
+            .and_then(|body| body.lines().next().map(str::to_string))
+            .unwrap_or_default();
+        if json::enabled() {
+            objects.push(json!({ "name": name, "source": source, "shebang": shebang }));
+        } else {
+            println!("{:<20} {:<26} {}", name, source, shebang);
+        }
+    }
+    if json::enabled() {
+        json::print(&objects.into());
+    }
+}
+
+fn validate_template_name(name: &str) {
+    if name.is_empty() || name.starts_with('.') || name.contains('/') {
+        json::fail("invalid-name", format!("Invalid template name '{}'", name));
+    }
+}
+
+fn add_template(name: &str, args: &[String]) {
+    validate_template_name(name);
+    let path = templates_dir().join(name);
+    if path.exists() {
+        let message = format!("Template '{}' already exists; use `fastbash templates edit {}`", name, name);
+        json::fail("already-exists", message);
+    }
+
+    let body = match args {
+        [] => load(DEFAULT_TEMPLATE).unwrap_or_default(),
+        [flag, file] if flag == "--from" => fs::read_to_string(file)
+            .unwrap_or_else(|err| json::fail("io", format!("Failed to read '{}': {}", file, err))),
+        _ => json::fail("usage", "Usage: fastbash templates add <name> [--from <file>]"),
+    };
+
+    fs::create_dir_all(templates_dir()).expect("Failed to create templates directory");
+    fs::write(&path, body).expect("Failed to write template");
+    if args.is_empty() {
+        open_in_editor(&path);
+    }
+    json::done(
+        json!({ "action": "templates add", "name": name, "path": path }),
+        format_args!("Template '{}' saved at {:?}", name, path),
+    );
+}
+
+fn edit_template(name: &str) {
+    validate_template_name(name);
+    let path = templates_dir().join(name);
+    if !path.exists() {
+        // Editing a built-in creates a user copy that overrides it.
+        let Some(body) = builtin(name) else {
+            json::fail("not-found", format!("Template '{}' not found", name));
+        };
+        fs::create_dir_all(templates_dir()).expect("Failed to create templates directory");
+        fs::write(&path, body).expect("Failed to write template");
+    }
+    open_in_editor(&path);
+}
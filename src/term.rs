@@ -0,0 +1,68 @@
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+use crate::json;
+
+/// Color is used only when stdout is a terminal and `NO_COLOR` is not set.
+pub fn color_enabled() -> bool {
+    env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stdout().is_terminal()
+}
+
+/// Width of the terminal stdout is connected to, or `None` when output is
+/// piped (and should not be cut to fit). `COLUMNS` overrides the detected width.
+pub fn width() -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(columns) = env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()) {
+        return Some(columns);
+    }
+    crossterm::terminal::size().ok().map(|(columns, _)| columns as usize)
+}
+
+/// Writes `text` to stdout. A reader that goes away early (`fastbash ls | head`)
+/// is not an error.
+pub fn print(text: &str) {
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(text.as_bytes()).and_then(|_| stdout.flush()) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => json::fail("io", format!("Failed to write output: {}", err)),
+    }
+}
+
+/// Shows `text` through `$PAGER` (default `less`) when stdout is a terminal,
+/// and writes it straight to stdout otherwise.
+pub fn page(text: &str) {
+    if io::stdout().is_terminal() && spawn_pager(text) {
+        return;
+    }
+    let _ = io::stdout().write_all(text.as_bytes());
+}
+
+fn spawn_pager(text: &str) -> bool {
+    let pager = env::var("PAGER").ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| "less".to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(bin) = parts.next() else {
+        return false;
+    };
+
+    let mut cmd = Command::new(bin);
+    cmd.args(parts).stdin(Stdio::piped());
+    // Keep colors, and don't page output that fits on one screen
+    if env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    let Ok(mut child) = cmd.spawn() else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
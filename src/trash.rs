@@ -0,0 +1,222 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{git, history, json, meta, store};
+
+const TRASH_DIR: &str = ".trash";
+const INFO_FILE: &str = "info.toml";
+
+/// What we remember about a removed script, stored next to it as `info.toml`.
+#[derive(Debug, Serialize, Deserialize)]
+struct TrashInfo {
+    name: String,
+    /// Where the script was when it was removed, for reference only.
+    original_path: PathBuf,
+    deleted_at: DateTime<Local>,
+}
+
+/// A removed script: `<store>/.trash/<id>/` holds the file and its `info.toml`.
+#[derive(Debug)]
+struct TrashEntry {
+    id: String,
+    dir: PathBuf,
+    /// Directory of the store the script was removed from.
+    root: PathBuf,
+    layer: store::LayerKind,
+    info: TrashInfo,
+}
+
+impl TrashEntry {
+    fn file(&self) -> PathBuf {
+        let base = self.info.name.rsplit('/').next().unwrap_or(&self.info.name);
+        self.dir.join(base)
+    }
+}
+
+/// Moves a script into its store's trash, returning the trash entry id.
+pub fn move_to_trash(script: &store::Script) -> io::Result<String> {
+    let trash = script.root.join(TRASH_DIR);
+    let now = Local::now();
+    let stamp = now.format("%Y%m%d-%H%M%S").to_string();
+    let mut id = stamp.clone();
+    let mut n = 1;
+    while trash.join(&id).exists() {
+        n += 1;
+        id = format!("{}-{}", stamp, n);
+    }
+
+    let dir = trash.join(&id);
+    fs::create_dir_all(&dir)?;
+    let info = TrashInfo {
+        name: script.name.clone(),
+        original_path: script.path.clone(),
+        deleted_at: now,
+    };
+    fs::write(dir.join(INFO_FILE), toml::to_string(&info).expect("Failed to serialize trash info"))?;
+    let base = script.name.rsplit('/').next().unwrap_or(&script.name);
+    fs::rename(&script.path, dir.join(base))?;
+    if let Err(err) = history::trash(&script.root, &script.name, &id) {
+        eprintln!("Warning: could not set aside the history of '{}': {}", script.name, err);
+    }
+    Ok(id)
+}
+
+/// Trash entries of every writable layer, newest first.
+fn entries() -> Vec<TrashEntry> {
+    let mut entries = Vec::new();
+    for layer in store::layers().into_iter().filter(|layer| !layer.kind.is_read_only()) {
+        let Ok(dirs) = fs::read_dir(layer.dir.join(TRASH_DIR)) else {
+            continue;
+        };
+        for dir in dirs.flatten() {
+            let path = dir.path();
+            let Some(info) = fs::read_to_string(path.join(INFO_FILE))
+                .ok()
+                .and_then(|contents| toml::from_str::<TrashInfo>(&contents).ok())
+            else {
+                continue;
+            };
+            entries.push(TrashEntry {
+                id: dir.file_name().to_string_lossy().to_string(),
+                dir: path,
+                root: layer.dir.clone(),
+                layer: layer.kind,
+                info,
+            });
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.info.deleted_at));
+    entries
+}
+
+pub fn trash_command(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None | Some("ls") => list_trash(),
+        Some("empty") => empty_trash(&args[1..]),
+        _ => json::fail("usage", "Usage: fastbash trash [ls | empty [--older-than <age>]]"),
+    }
+}
+
+fn list_trash() {
+    let entries = entries();
+    if json::enabled() {
+        let objects: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "id": entry.id,
+                    "name": entry.info.name,
+                    "original_path": entry.info.original_path,
+                    "deleted_at": entry.info.deleted_at,
+                    "store": entry.layer.to_string(),
+                })
+            })
+            .collect();
+        json::print(&objects.into());
+        return;
+    }
+    if entries.is_empty() {
+        println!("Trash is empty");
+        return;
+    }
+    for entry in entries {
+        let layer = match entry.layer {
+            store::LayerKind::User => String::new(),
+            layer => format!("  [{}]", layer),
+        };
+        println!(
+            "{:<20} {:<20} {}{}",
+            entry.id,
+            entry.info.name,
+            entry.info.deleted_at.format("%Y-%m-%d %H:%M"),
+            layer
+        );
+    }
+}
+
+fn empty_trash(args: &[String]) {
+    let older_than = match args {
+        [] => None,
+        [flag, age] if flag == "--older-than" => match meta::parse_duration(age) {
+            Some(age) => Some(age),
+            None => json::fail("invalid-argument", format!("Invalid age '{}'; use e.g. 30d, 12h or 90m", age)),
+        },
+        _ => json::fail("usage", "Usage: fastbash trash empty [--older-than <age>]"),
+    };
+
+    let now = Local::now();
+    let mut removed = 0;
+    for entry in entries() {
+        let age = (now - entry.info.deleted_at).to_std().unwrap_or_default();
+        if older_than.is_some_and(|limit| age < limit) {
+            continue;
+        }
+        if let Err(err) = fs::remove_dir_all(&entry.dir) {
+            eprintln!("Failed to remove {}: {}", entry.dir.display(), err);
+            continue;
+        }
+        if let Err(err) = history::forget_trashed(&entry.root, &entry.id) {
+            eprintln!("Warning: could not remove the history of '{}': {}", entry.info.name, err);
+        }
+        removed += 1;
+    }
+    json::done(
+        json!({ "action": "trash empty", "removed": removed }),
+        format_args!("Permanently removed {} script(s) from the trash", removed),
+    );
+}
+
+/// `restore <name|id>` puts back the most recently removed match.
+pub fn restore_command(args: &[String]) {
+    let [target] = args else {
+        json::fail("usage", "Usage: fastbash restore <script|trash-id>");
+    };
+
+    let entries = entries();
+    let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.id == *target)
+        .or_else(|| entries.iter().find(|entry| entry.info.name == *target))
+    else {
+        json::fail("not-found", format!("No script '{}' in the trash; see `fastbash trash ls`", target));
+    };
+
+    if let Err(err) = store::validate_name(&entry.info.name) {
+        json::fail("invalid-name", format!("Cannot restore trash entry {}: {}", entry.id, err));
+    }
+    // Back into the store the entry was found in; the store may have moved
+    // since, so `original_path` is only informational
+    let destination = &entry.root.join(&entry.info.name);
+    if destination.exists() {
+        json::fail("already-exists", format!(
+            "Cannot restore '{}': a script already exists at {}",
+            entry.info.name,
+            destination.display()
+        ));
+    }
+    if let Err(err) = restore(entry, destination) {
+        json::fail("io", format!("Failed to restore '{}': {}", entry.info.name, err));
+    }
+    git::auto_commit(&entry.root, &[&entry.info.name], &format!("restore {}", entry.info.name));
+    json::done(
+        json!({ "action": "restore", "name": entry.info.name, "path": destination, "trash_id": entry.id }),
+        format_args!("Restored script '{}'", entry.info.name),
+    );
+}
+
+fn restore(entry: &TrashEntry, destination: &Path) -> io::Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(entry.file(), destination)?;
+    if let Err(err) = history::restore(&entry.root, &entry.id, &entry.info.name) {
+        eprintln!("Warning: could not restore the history of '{}': {}", entry.info.name, err);
+    }
+    fs::remove_dir_all(&entry.dir)
+}